@@ -0,0 +1,20 @@
+use super::CommandResult;
+use crate::daemon::{DaemonClient, DaemonRequest};
+use crate::repositories::Backup;
+use crate::services::create_default_backup_service;
+use crate::types::*;
+use std::path::PathBuf;
+
+pub fn handle_list_backups() -> CommandResult<Vec<Backup>> {
+    create_default_backup_service()?.list_backups()
+}
+
+/// Rolls `registry.ron` and `watchers.ron` back to how they were at `timestamp`, then has a
+/// running daemon pick up the restored watchers.
+pub fn handle_restore_backup(timestamp: BackupTimestamp) -> CommandResult<Vec<PathBuf>> {
+    let restored = create_default_backup_service()?.restore(timestamp)?;
+    if let Some(client) = DaemonClient::connect()? {
+        client.send(&DaemonRequest::Reload)?;
+    }
+    Ok(restored)
+}
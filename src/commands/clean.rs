@@ -2,24 +2,62 @@ use crate::types::*;
 use crate::services::create_default_project_service;
 use super::{CommandResult};
 
-/// Calls `cargo clean` in the directory in which the specified ProjectId is. Does the same as `cargo clean` because its called.
-pub fn handle_clean_project(id: ProjectId) ->
+pub struct CleanResult {
+    pub cleaned: Vec<RustProject>,
+    /// Pinned projects left alone because the clean was not forced
+    pub skipped: Vec<RustProject>,
+    pub failed: Vec<(RustProject, ProjectsError)>,
+}
+
+/// Calls `cargo clean` in the directory of the specified project. Does the same as `cargo clean` because its called.
+/// A pinned project is refused unless `force` is set.
+pub fn handle_clean_project(project: &ProjectRef, force: bool) ->
   CommandResult<String> {
       create_default_project_service()?
-          .get_project_by_id(id)?
-          .ok_or(ProjectsError::ProjectNotFound { id })
-          .and_then(|project| {
-              std::process::Command::new("cargo")
-                  .arg("clean")
-                  .current_dir(&project.path)
-                  .output()
-                  .map_err(Into::into)
-                  .and_then(|output| output.status.success()
-                      .then_some(project.name.to_string())
-                      .ok_or_else(||
-  ProjectsError::CargoCommandFailed {
-                          stderr:
-  String::from_utf8_lossy(&output.stderr).to_string()
-                      }))
-          })
-}
\ No newline at end of file
+          .resolve_project(project)
+          .and_then(|project| cargo_clean(&project, force).map(|()| project.name.to_string()))
+}
+
+/// Calls `cargo clean` in every project carrying all of `tags`, going on after a failure.
+/// Pinned projects are skipped unless `force` is set.
+pub fn handle_clean_tagged_projects(tags: &[Tag], force: bool) -> CommandResult<CleanResult> {
+    let mut projects = create_default_project_service()?.get_all_projects()?;
+    projects.retain(|project| project.annotations.has_tags(tags));
+    projects.sort_by_key(|project| project.id);
+
+    let mut result = CleanResult {
+        cleaned: Vec::new(),
+        skipped: Vec::new(),
+        failed: Vec::new(),
+    };
+    for project in projects {
+        match cargo_clean(&project, force) {
+            Ok(()) => result.cleaned.push(project),
+            Err(ProjectsError::ProjectPinned { .. }) => result.skipped.push(project),
+            Err(e) => result.failed.push((project, e)),
+        }
+    }
+    Ok(result)
+}
+
+/// Every clean goes through here, so no caller can wipe the `target/` of a pinned project
+/// without forcing it.
+fn cargo_clean(project: &RustProject, force: bool) -> ProjectsResult<()> {
+    if project.annotations.pinned && !force {
+        return Err(ProjectsError::ProjectPinned {
+            id: project.id,
+            name: project.name.clone(),
+        });
+    }
+
+    std::process::Command::new("cargo")
+        .arg("clean")
+        .current_dir(&project.path)
+        .output()
+        .map_err(Into::into)
+        .and_then(|output| output.status.success()
+            .then_some(())
+            .ok_or_else(|| ProjectsError::CargoCommandFailed {
+                stderr: String::from_utf8_lossy(&output.stderr).to_string()
+            }))
+}
@@ -1,5 +1,7 @@
 use clap::Parser;
-use crate::types::{WatcherName, ProjectId};
+use crate::commands::export::RegistryFormat;
+use crate::commands::watchers::WatcherEdit;
+use crate::types::{BackupTimestamp, PathRewrite, WatcherName, ProjectRef, Tag, TagEdit, WatchMode, WatchRules};
 
 #[derive(Parser)]
 #[command(name = "cargo")]
@@ -24,21 +26,44 @@ pub const CLAP_STYLING: clap::builder::styling::Styles = clap::builder::styling:
 pub struct ProjectsArgs {
     #[command(subcommand)]
     pub command: ProjectsCommand,
+    /// Keep config.ron and the registries in DIR, defaults to `$CARGO_PROJECTS_HOME`
+    #[arg(long, global = true, value_name = "DIR")]
+    pub config_dir: Option<std::path::PathBuf>,
+    /// Use a separate registry kept in profiles/NAME below the config directory, defaults to
+    /// `$CARGO_PROJECTS_PROFILE`
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
 }
 
 #[derive(clap::Subcommand)]
 pub enum ProjectsCommand {
     List {
         watcher_name: Option<WatcherName>,
+        /// Only list projects carrying this tag, repeat to require several
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<Tag>,
+        /// Show the member crates of each workspace below it
+        #[arg(long)]
+        members: bool,
+    },
+    Watchers {
+        #[command(subcommand)]
+        action: Option<WatchersCommand>,
     },
-    Watchers,
     CleanWatchers,
     Scan {
         #[arg(short, long, default_value = ".")]
         path_to_directory_watched: std::path::PathBuf,
     },
     Clean {
-        project_id: ProjectId,
+        #[arg(required_unless_present = "tags", conflicts_with = "tags")]
+        project: Option<ProjectRef>,
+        /// Clean every project carrying this tag instead, repeat to require several
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<Tag>,
+        /// Clean pinned projects too
+        #[arg(long)]
+        force: bool,
     },
     Watch {
         #[arg(short, long, default_value = ".")]
@@ -47,9 +72,237 @@ pub enum ProjectsCommand {
         name: Option<WatcherName>,
         #[arg(long)]
         system_wide: bool,
+        #[command(flatten)]
+        rules: WatchRulesArgs,
+        #[command(flatten)]
+        mode: WatchModeArgs,
+    },
+    Daemon {
+        #[command(subcommand)]
+        action: Option<DaemonCommand>,
     },
     Update,
     Refresh,
+    /// Show how the size and target size of a project grew over time
+    History {
+        project: ProjectRef,
+    },
+    /// Add tags with +TAG and remove them with -TAG, e.g. `tag 3 +game -archived`
+    Tag {
+        project: ProjectRef,
+        #[arg(required = true, allow_hyphen_values = true, value_name = "+TAG|-TAG")]
+        edits: Vec<TagEdit>,
+    },
+    /// Protect a project from `clean` unless it is forced
+    Pin {
+        project: ProjectRef,
+    },
+    /// Let `clean` wipe a pinned project again
+    Unpin {
+        project: ProjectRef,
+    },
+    /// Show the note of a project, or replace it with TEXT
+    Note {
+        project: ProjectRef,
+        text: Option<String>,
+        /// Remove the note
+        #[arg(long, conflicts_with = "text")]
+        clear: bool,
+    },
+    /// Untrack projects whose directory is gone or no longer contains a Cargo.toml
+    Prune {
+        /// Only list the projects that would be untracked
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Untrack a project, leaving its directory alone
+    Remove {
+        project: ProjectRef,
+    },
+    /// Give the projects contiguous IDs starting at 1, keeping their order
+    Renumber,
+    /// Write every project and watcher to a file that can be imported on another machine
+    Export {
+        /// Defaults to the extension of --output, then to JSON
+        #[arg(long, value_enum)]
+        format: Option<RegistryFormat>,
+        /// Write to this file instead of printing
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Read projects and watchers written by `export`
+    Import {
+        file: std::path::PathBuf,
+        /// Defaults to the extension of the file
+        #[arg(long, value_enum)]
+        format: Option<RegistryFormat>,
+        /// Move paths below FROM to TO, e.g. `/home/alice=/home/bob`; the first match applies
+        #[arg(long = "rewrite", value_name = "FROM=TO")]
+        rewrites: Vec<PathRewrite>,
+        /// Forget all tracked projects and watchers instead of merging into them
+        #[arg(long)]
+        replace: bool,
+    },
+    /// List or restore the backups of registry.ron and watchers.ron
+    Backup {
+        #[command(subcommand)]
+        action: Option<BackupCommand>,
+    },
     //ResetRegistry,
     //ResetWatchers,
-}
\ No newline at end of file
+}
+
+#[derive(clap::Subcommand)]
+pub enum DaemonCommand {
+    /// Run the daemon in the foreground (default)
+    Run,
+    /// Show the watchers the running daemon is watching
+    Status,
+    /// Make the running daemon pick up watcher changes
+    Reload,
+    /// Stop the running daemon
+    Stop,
+    /// Install and start a systemd user service running the daemon
+    Install {
+        /// Print the unit file instead of installing it
+        #[arg(long)]
+        print: bool,
+    },
+    /// Stop and remove the systemd user service
+    Uninstall,
+}
+
+#[derive(clap::Subcommand)]
+pub enum BackupCommand {
+    /// List the backups, oldest first (default)
+    List,
+    /// Roll the registries back to how they were at TIMESTAMP, as shown by `backup list`
+    Restore { timestamp: BackupTimestamp },
+}
+
+#[derive(clap::Subcommand)]
+pub enum WatchersCommand {
+    /// List all watchers (default)
+    List,
+    /// Watch a directory for new projects, same as `cargo projects watch`
+    Add {
+        name: Option<WatcherName>,
+        #[arg(short, long, default_value = ".")]
+        path: std::path::PathBuf,
+        #[command(flatten)]
+        rules: WatchRulesArgs,
+        #[command(flatten)]
+        mode: WatchModeArgs,
+    },
+    /// Stop watching and forget a watcher
+    Remove { name: WatcherName },
+    /// Give a watcher a new name
+    Rename {
+        name: WatcherName,
+        new_name: WatcherName,
+    },
+    /// Change the path or rules of a watcher
+    Edit {
+        name: WatcherName,
+        #[command(flatten)]
+        edit: WatcherEditArgs,
+    },
+    /// Show a watcher with its projects and their combined size
+    Show { name: WatcherName },
+    /// Stop watching without forgetting the watcher
+    Pause { name: WatcherName },
+    /// Watch a paused watcher again
+    Resume { name: WatcherName },
+}
+
+#[derive(clap::Args)]
+pub struct WatchRulesArgs {
+    /// Only track projects whose path below the watcher matches one of these globs
+    #[arg(long = "include", value_name = "GLOB")]
+    include: Vec<String>,
+    /// Never search directories matching these globs, e.g. `vendor` or `tests/fixtures`
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+    /// Deepest directory level searched for `Cargo.toml` files (default `max_scan_depth` from config.ron)
+    #[arg(long)]
+    max_depth: Option<usize>,
+    /// Descend into symlinked directories
+    #[arg(long)]
+    follow_symlinks: bool,
+}
+
+impl From<WatchRulesArgs> for WatchRules {
+    fn from(args: WatchRulesArgs) -> Self {
+        Self {
+            include: args.include,
+            exclude: args.exclude,
+            max_depth: args.max_depth,
+            follow_symlinks: args.follow_symlinks,
+        }
+    }
+}
+
+#[derive(clap::Args)]
+pub struct WatchModeArgs {
+    /// Poll for changes every SECS seconds instead of relying on native filesystem events,
+    /// for NFS or SSHFS mounts and containers
+    #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    poll: Option<u64>,
+}
+
+impl From<WatchModeArgs> for WatchMode {
+    fn from(args: WatchModeArgs) -> Self {
+        args.poll
+            .map_or(Self::Native, |interval_secs| Self::Poll { interval_secs })
+    }
+}
+
+#[derive(clap::Args)]
+pub struct WatcherEditArgs {
+    /// Move the watcher to another directory
+    #[arg(short, long)]
+    path: Option<std::path::PathBuf>,
+    /// Replace the include globs
+    #[arg(long = "include", value_name = "GLOB", conflicts_with = "clear_include")]
+    include: Vec<String>,
+    /// Remove all include globs
+    #[arg(long)]
+    clear_include: bool,
+    /// Replace the exclude globs
+    #[arg(long = "exclude", value_name = "GLOB", conflicts_with = "clear_exclude")]
+    exclude: Vec<String>,
+    /// Remove all exclude globs
+    #[arg(long)]
+    clear_exclude: bool,
+    /// Deepest directory level searched for `Cargo.toml` files
+    #[arg(long)]
+    max_depth: Option<usize>,
+    /// Whether to descend into symlinked directories
+    #[arg(long, value_name = "BOOL")]
+    follow_symlinks: Option<bool>,
+    /// Switch to polling every SECS seconds
+    #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    poll: Option<u64>,
+    /// Switch back to native filesystem events
+    #[arg(long, conflicts_with = "poll")]
+    native: bool,
+}
+
+impl From<WatcherEditArgs> for WatcherEdit {
+    fn from(args: WatcherEditArgs) -> Self {
+        let replace = |globs: Vec<String>, clear: bool| (clear || !globs.is_empty()).then_some(globs);
+
+        Self {
+            path: args.path,
+            include: replace(args.include, args.clear_include),
+            exclude: replace(args.exclude, args.clear_exclude),
+            max_depth: args.max_depth,
+            follow_symlinks: args.follow_symlinks,
+            mode: match (args.poll, args.native) {
+                (Some(interval_secs), _) => Some(WatchMode::Poll { interval_secs }),
+                (None, true) => Some(WatchMode::Native),
+                (None, false) => None,
+            },
+        }
+    }
+}
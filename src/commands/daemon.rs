@@ -0,0 +1,73 @@
+use super::CommandResult;
+use crate::daemon::control::socket_path;
+use crate::daemon::systemd;
+use crate::daemon::{
+    DaemonClient, DaemonRequest, DaemonResponse, DaemonStatus, DaemonSummary, run_daemon,
+};
+use crate::services::{
+    create_default_config_service, create_default_project_service, create_default_watcher_service,
+};
+use crate::types::*;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Runs the watcher daemon in the foreground until `cargo projects daemon stop` is called.
+pub fn handle_daemon_run() -> CommandResult<DaemonSummary> {
+    let config = create_default_config_service()?.get_config()?;
+    let service = create_default_project_service()?;
+    let watcher_service = create_default_watcher_service()?;
+    run_daemon(
+        &service,
+        &watcher_service,
+        &socket_path()?,
+        Duration::from_secs(config.size_reconcile_interval_secs),
+        Duration::from_secs(config.fallback_poll_interval_secs),
+    )
+}
+
+pub fn handle_daemon_status() -> CommandResult<DaemonStatus> {
+    match send_to_daemon(&DaemonRequest::Status)? {
+        DaemonResponse::Status(status) => Ok(status),
+        response => Err(unexpected_response(&response)),
+    }
+}
+
+/// Makes the daemon re-read `watchers.ron` and the scan settings of `config.ron` without restarting it.
+pub fn handle_daemon_reload() -> CommandResult {
+    send_to_daemon(&DaemonRequest::Reload).map(|_| ())
+}
+
+pub fn handle_daemon_stop() -> CommandResult {
+    send_to_daemon(&DaemonRequest::Stop).map(|_| ())
+}
+
+pub enum InstallResult {
+    Printed(String),
+    Installed(PathBuf),
+}
+
+/// Writes the systemd user unit and enables it, or only renders it with `print`.
+pub fn handle_daemon_install(print: bool) -> CommandResult<InstallResult> {
+    let unit = systemd::current_unit()?;
+    if print {
+        return Ok(InstallResult::Printed(unit));
+    }
+    systemd::install_unit(&unit).map(InstallResult::Installed)
+}
+
+/// Returns the removed unit path, `None` when no unit was installed.
+pub fn handle_daemon_uninstall() -> CommandResult<Option<PathBuf>> {
+    systemd::uninstall_unit()
+}
+
+fn send_to_daemon(request: &DaemonRequest) -> CommandResult<DaemonResponse> {
+    DaemonClient::connect()?
+        .ok_or(ProjectsError::DaemonNotRunning)
+        .and_then(|client| client.send(request))
+}
+
+fn unexpected_response(response: &DaemonResponse) -> ProjectsError {
+    ProjectsError::Daemon {
+        message: format!("unexpected response {response:?}"),
+    }
+}
@@ -0,0 +1,154 @@
+use super::CommandResult;
+use crate::services::{create_default_project_service, create_default_watcher_service};
+use crate::types::*;
+use chrono::Utc;
+use ron::ser::PrettyConfig;
+use std::path::{Path, PathBuf};
+
+/// File formats written by `export` and read by `import`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RegistryFormat {
+    Json,
+    Toml,
+    Ron,
+}
+
+impl RegistryFormat {
+    /// The format a file extension stands for.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            "ron" => Some(Self::Ron),
+            _ => None,
+        }
+    }
+
+    pub fn serialize(self, export: &RegistryExport) -> ProjectsResult<String> {
+        match self {
+            Self::Json => serde_json::to_string_pretty(export).map_err(Into::into),
+            Self::Toml => toml::to_string_pretty(export).map_err(Into::into),
+            Self::Ron => ron::ser::to_string_pretty(export, PrettyConfig::default()).map_err(Into::into),
+        }
+    }
+
+    pub fn deserialize(self, content: &str) -> ProjectsResult<RegistryExport> {
+        match self {
+            Self::Json => serde_json::from_str(content).map_err(Into::into),
+            Self::Toml => toml::from_str(content).map_err(Into::into),
+            Self::Ron => ron::from_str(content).map_err(Into::into),
+        }
+    }
+}
+
+pub enum ExportResult {
+    Printed(String),
+    Written {
+        path: PathBuf,
+        projects: usize,
+        watchers: usize,
+    },
+}
+
+/// Serializes every project and watcher into `output`, or for printing without one. The format
+/// defaults to the extension of `output`, then to JSON.
+pub fn handle_export_registry(
+    format: Option<RegistryFormat>,
+    output: Option<&Path>,
+) -> CommandResult<ExportResult> {
+    let format = format
+        .or_else(|| output.and_then(RegistryFormat::from_path))
+        .unwrap_or(RegistryFormat::Json);
+
+    let mut projects = create_default_project_service()?.get_all_projects()?;
+    projects.sort_by_key(|project| project.id);
+    // Health describes what one daemon saw on one machine
+    let mut watchers: Vec<WatcherConfig> = create_default_watcher_service()?
+        .get_all_watchers()?
+        .into_iter()
+        .map(|watcher| WatcherConfig {
+            health: WatcherHealth::default(),
+            ..watcher
+        })
+        .collect();
+    watchers.sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+
+    let export = RegistryExport {
+        schema_version: PROJECT_REGISTRY_VERSION,
+        exported_at: Utc::now(),
+        projects,
+        watchers,
+    };
+    let content = format.serialize(&export)?;
+
+    match output {
+        None => Ok(ExportResult::Printed(content)),
+        Some(path) => {
+            std::fs::write(path, content)?;
+            Ok(ExportResult::Written {
+                path: path.to_path_buf(),
+                projects: export.projects.len(),
+                watchers: export.watchers.len(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_format_round_trips_projects_and_watchers() -> ProjectsResult<()> {
+        let export = RegistryExport {
+            schema_version: PROJECT_REGISTRY_VERSION,
+            exported_at: Utc::now(),
+            projects: vec![RustProject {
+                id: ProjectId::new(3),
+                name: ProjectName::new("game".to_string()),
+                path: PathBuf::from("/home/alice/dev/game"),
+                version: ProjectVersion::new("0.1.0".to_string()),
+                created_at: Utc::now(),
+                last_modified: Utc::now(),
+                size_bytes: FileSize::new(1000),
+                target_size_bytes: FileSize::new(500),
+                dependencies_count: DependencyCount::new(2),
+                estimated_build_time_seconds: TimingDuration::new(30),
+                project_type: ProjectType::WorkspaceWithPackage,
+                annotations: ProjectAnnotations::default(),
+                members: vec![WorkspaceMember {
+                    name: ProjectName::new("game-core".to_string()),
+                    path: PathBuf::from("/home/alice/dev/game/crates/core"),
+                    version: ProjectVersion::new("0.1.0".to_string()),
+                    size_bytes: FileSize::new(200),
+                }],
+            }],
+            watchers: vec![WatcherConfig {
+                name: WatcherName::new("dev".to_string()),
+                path: PathBuf::from("/home/alice/dev"),
+                created_at: Utc::now(),
+                system_managed: false,
+                rules: WatchRules::default(),
+                mode: WatchMode::Poll { interval_secs: 60 },
+                paused: true,
+                health: WatcherHealth::default(),
+            }],
+        };
+
+        for format in [RegistryFormat::Json, RegistryFormat::Toml, RegistryFormat::Ron] {
+            let imported = format.deserialize(&format.serialize(&export)?)?;
+
+            assert_eq!(imported.projects[0].id, ProjectId::new(3));
+            assert_eq!(imported.projects[0].path, export.projects[0].path);
+            assert_eq!(imported.projects[0].members, export.projects[0].members);
+            assert_eq!(imported.watchers[0].mode, WatchMode::Poll { interval_secs: 60 });
+            assert!(imported.watchers[0].paused);
+
+            let rewrites = ["/home/alice=/home/bob".parse::<PathRewrite>().unwrap()];
+            let moved = crate::commands::import::rewrite_project(imported.projects[0].clone(), &rewrites);
+            assert_eq!(moved.path, PathBuf::from("/home/bob/dev/game"));
+            assert!(moved.has_member(Path::new("/home/bob/dev/game/crates/core")));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+use super::CommandResult;
+use crate::services::create_default_project_service;
+use crate::types::*;
+
+pub struct HistoryResult {
+    pub project: RustProject,
+    /// Oldest first
+    pub snapshots: Vec<SizeSnapshot>,
+}
+
+/// Shows how the sizes of a project changed across `update`, scans and daemon refreshes.
+pub fn handle_project_history(project: &ProjectRef) -> CommandResult<HistoryResult> {
+    let service = create_default_project_service()?;
+    let project = service.resolve_project(project)?;
+    let snapshots = service.get_size_history(project.id)?;
+
+    Ok(HistoryResult { project, snapshots })
+}
@@ -0,0 +1,74 @@
+use super::export::RegistryFormat;
+use super::{CommandResult, import_watchers};
+use crate::services::create_default_project_service;
+use crate::types::*;
+use std::path::{Path, PathBuf};
+
+pub struct ImportResult {
+    pub projects: usize,
+    pub watchers: usize,
+    /// Projects whose exported id was already taken by another project
+    pub renumbered: usize,
+    pub mode: ImportMode,
+}
+
+/// Reads a file written by `export`, moves its paths with the first matching rewrite and merges
+/// it into the registry or replaces the registry with it.
+pub fn handle_import_registry(
+    file: &Path,
+    format: Option<RegistryFormat>,
+    rewrites: &[PathRewrite],
+    mode: ImportMode,
+) -> CommandResult<ImportResult> {
+    let format = format
+        .or_else(|| RegistryFormat::from_path(file))
+        .ok_or_else(|| ProjectsError::UnknownRegistryFormat {
+            path: file.to_path_buf(),
+        })?;
+    let export = format.deserialize(&std::fs::read_to_string(file)?)?;
+    if export.schema_version > PROJECT_REGISTRY_VERSION {
+        return Err(ProjectsError::SchemaTooNew {
+            path: file.to_path_buf(),
+            found: export.schema_version,
+            supported: PROJECT_REGISTRY_VERSION,
+        });
+    }
+
+    let projects: Vec<RustProject> = export
+        .projects
+        .into_iter()
+        .map(|project| rewrite_project(project, rewrites))
+        .collect();
+    let watchers: Vec<WatcherConfig> = export
+        .watchers
+        .into_iter()
+        .map(|watcher| WatcherConfig {
+            path: rewrite(&watcher.path, rewrites),
+            health: WatcherHealth::default(),
+            ..watcher
+        })
+        .collect();
+
+    let (project_count, watcher_count) = (projects.len(), watchers.len());
+    let renumbered = create_default_project_service()?.import_projects(projects, mode)?;
+    import_watchers(watchers, mode)?;
+
+    Ok(ImportResult {
+        projects: project_count,
+        watchers: watcher_count,
+        renumbered,
+        mode,
+    })
+}
+
+/// Moves the project and its workspace members with the first matching rewrite.
+pub(super) fn rewrite_project(project: RustProject, rewrites: &[PathRewrite]) -> RustProject {
+    project.with_paths_mapped(|path| rewrite(path, rewrites))
+}
+
+fn rewrite(path: &Path, rewrites: &[PathRewrite]) -> PathBuf {
+    rewrites
+        .iter()
+        .find_map(|rewrite| rewrite.apply(path))
+        .unwrap_or_else(|| path.to_path_buf())
+}
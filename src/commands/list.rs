@@ -1,14 +1,19 @@
 use super::CommandResult;
 use crate::services::create_default_project_service;
 use crate::types::*;
+use std::collections::HashSet;
 
 pub struct ProjectListResult {
     pub projects: Vec<RustProject>,
     pub total_count: usize,
+    /// Projects whose directory no longer exists on disk
+    pub missing: HashSet<ProjectId>,
 }
 
+/// Lists the projects of a watcher, or all of them, keeping only those carrying every tag.
 pub fn handle_list_projects(
     watcher_name: Option<&WatcherName>,
+    tags: &[Tag],
 ) -> CommandResult<ProjectListResult> {
     create_default_project_service()
         .and_then(|service| {
@@ -17,9 +22,15 @@ pub fn handle_list_projects(
             })
         })
         .map(|mut projects| {
+            projects.retain(|p| p.annotations.has_tags(tags));
             projects.sort_by_key(|p| p.id);
             ProjectListResult {
                 total_count: projects.len(),
+                missing: projects
+                    .iter()
+                    .filter(|p| !p.path.exists())
+                    .map(|p| p.id)
+                    .collect(),
                 projects,
             }
         })
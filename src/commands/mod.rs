@@ -1,10 +1,22 @@
 pub mod shared;
 pub mod cli;
 pub mod list;
+pub mod backup;
 pub mod clean;
 pub mod clean_watchers;
+pub mod daemon;
+pub mod export;
+pub mod history;
+pub mod import;
+pub mod note;
+pub mod pin;
+pub mod prune;
 pub mod refresh;
+pub mod remove;
+pub mod renumber;
 pub mod scan;
+pub mod system_wide;
+pub mod tag;
 pub mod update;
 pub mod watchers;
 
@@ -14,11 +26,23 @@ use crate::types::{ProjectsResult};
 pub type CommandResult<T = ()> = ProjectsResult<T>;
 
 pub use list::handle_list_projects;
-pub use clean::handle_clean_project;
+pub use backup::{handle_list_backups, handle_restore_backup};
+pub use clean::{handle_clean_project, handle_clean_tagged_projects};
 pub use clean_watchers::handle_clean_watchers;
+pub use daemon::{handle_daemon_run, handle_daemon_status, handle_daemon_reload, handle_daemon_stop, handle_daemon_install, handle_daemon_uninstall};
+pub use export::handle_export_registry;
+pub use history::handle_project_history;
+pub use import::handle_import_registry;
+pub use note::{handle_set_note, handle_show_note};
+pub use pin::handle_pin_project;
+pub use prune::handle_prune_projects;
 pub use refresh::handle_refresh_timing;
+pub use remove::handle_remove_project;
+pub use renumber::handle_renumber_projects;
 pub use scan::handle_scan_directory;
+pub use system_wide::handle_watch_system_wide;
+pub use tag::handle_tag_project;
 pub use update::handle_update_projects;
-pub use watchers::handle_list_watchers;
+pub use watchers::{handle_list_watchers, handle_add_watcher, handle_remove_watcher, handle_rename_watcher, handle_edit_watcher, handle_show_watcher, handle_pause_watcher, handle_resume_watcher};
 
 pub use shared::*;
\ No newline at end of file
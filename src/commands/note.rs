@@ -0,0 +1,17 @@
+use super::CommandResult;
+use crate::services::create_default_project_service;
+use crate::types::*;
+
+/// Replaces the note of a project, `None` or an empty note removes it.
+pub fn handle_set_note(project: &ProjectRef, note: Option<String>) -> CommandResult<RustProject> {
+    let note = note.filter(|note| !note.trim().is_empty());
+    let service = create_default_project_service()?;
+    let id = service.resolve_project(project)?.id;
+    service
+        .annotate_project(id, &|annotations| annotations.note.clone_from(&note))?
+        .ok_or(ProjectsError::ProjectNotFound { id })
+}
+
+pub fn handle_show_note(project: &ProjectRef) -> CommandResult<RustProject> {
+    create_default_project_service()?.resolve_project(project)
+}
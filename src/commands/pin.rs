@@ -0,0 +1,12 @@
+use super::CommandResult;
+use crate::services::create_default_project_service;
+use crate::types::*;
+
+/// Pins or unpins a project, pinned projects are only cleaned with `--force`.
+pub fn handle_pin_project(project: &ProjectRef, pinned: bool) -> CommandResult<RustProject> {
+    let service = create_default_project_service()?;
+    let id = service.resolve_project(project)?.id;
+    service
+        .annotate_project(id, &|annotations| annotations.pinned = pinned)?
+        .ok_or(ProjectsError::ProjectNotFound { id })
+}
@@ -0,0 +1,28 @@
+use super::CommandResult;
+use crate::services::create_default_project_service;
+use crate::types::*;
+
+pub struct PruneResult {
+    /// Projects whose directory is gone or no longer holds a cargo project, by id
+    pub pruned: Vec<(RustProject, PruneReason)>,
+    /// The projects were only listed, not removed
+    pub dry_run: bool,
+}
+
+/// Untracks every project deleted or moved outside of a watcher, along with its size history.
+pub fn handle_prune_projects(dry_run: bool) -> CommandResult<PruneResult> {
+    let service = create_default_project_service()?;
+    let mut pruned: Vec<(RustProject, PruneReason)> = service
+        .get_all_projects()?
+        .into_iter()
+        .filter_map(|project| PruneReason::of(&project.path).map(|reason| (project, reason)))
+        .collect();
+    pruned.sort_by_key(|(project, _)| project.id);
+
+    if !dry_run {
+        let ids: Vec<ProjectId> = pruned.iter().map(|(project, _)| project.id).collect();
+        service.remove_projects(&ids)?;
+    }
+
+    Ok(PruneResult { pruned, dry_run })
+}
@@ -0,0 +1,12 @@
+use super::CommandResult;
+use crate::services::create_default_project_service;
+use crate::types::*;
+
+/// Stops tracking a project without touching its directory, returning the removed project.
+pub fn handle_remove_project(project: &ProjectRef) -> CommandResult<RustProject> {
+    let service = create_default_project_service()?;
+    let found = service.resolve_project(project)?;
+
+    service.remove_project(found.id)?;
+    Ok(found)
+}
@@ -0,0 +1,17 @@
+use super::CommandResult;
+use crate::services::create_default_project_service;
+
+pub struct RenumberResult {
+    pub renumbered: usize,
+    pub total: usize,
+}
+
+/// Gives the tracked projects the ids `1..=n`, keeping their order. Ids are otherwise kept for as
+/// long as a path is tracked, so removed projects leave gaps.
+pub fn handle_renumber_projects() -> CommandResult<RenumberResult> {
+    let service = create_default_project_service()?;
+    let renumbered = service.renumber_projects()?;
+    let total = service.get_all_projects()?.len();
+
+    Ok(RenumberResult { renumbered, total })
+}
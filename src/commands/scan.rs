@@ -1,14 +1,18 @@
 use super::{
-    CommandResult, create_malformed_project, create_package_project, create_workspace_project,
+    CommandResult, ScanSettings, WatchFilter, covering_watcher, create_malformed_project,
+    create_package_project, create_workspace_project, read_workspace_members,
+};
+use crate::repositories::{ProjectRepository, WatcherRepository};
+use crate::services::{
+    create_cached_project_service, create_default_watcher_service, ProjectService,
 };
-use crate::services::create_default_project_service;
 use crate::types::*;
 use log::{info, warn};
 use rayon::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use ignore::WalkBuilder;
-use std::sync::mpsc;
+use std::sync::{Arc, mpsc};
 use std::thread;
 
 pub struct ScanResult {
@@ -25,17 +29,46 @@ impl From<Vec<RustProject>> for ScanResult {
     }
 }
 
+/// Scans with the rules of the watcher covering `path`, if there is one.
 pub fn handle_scan_directory(path: &Path) -> CommandResult<ScanResult> {
-    let service = create_default_project_service()?;
+    let service = create_cached_project_service()?;
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
     info!("Scanning for Cargo projects in: {}", path.display());
 
-    let (new_projects, existing): (Vec<_>, Vec<_>) = collect_project_paths(path)?
+    let watchers = create_default_watcher_service()?.get_all_watchers()?;
+    let filter = match covering_watcher(&watchers, &path) {
+        Some(watcher) => {
+            info!("Applying the rules of watcher {}", watcher.name);
+            WatchFilter::new(&watcher.path, &watcher.rules, &ScanSettings::current())?
+        }
+        None => WatchFilter::default_for(&path, &ScanSettings::current()),
+    };
+
+    let result = track_project_paths(&service, collect_project_paths(&path, &filter, &[])?)?;
+    service.flush()?;
+    Ok(result)
+}
+
+/// Adds every path that is not yet in the registry as a new project. Workspace members are
+/// tracked as part of their workspace rather than as projects of their own.
+pub fn track_project_paths<P: ProjectRepository + ?Sized, W: WatcherRepository + ?Sized>(
+    service: &ProjectService<P, W>,
+    project_paths: Vec<PathBuf>,
+) -> CommandResult<ScanResult> {
+    refresh_tracked_workspaces(service, &project_paths)?;
+    let workspaces: Vec<RustProject> = service
+        .get_all_projects()?
+        .into_iter()
+        .filter(|project| !project.members.is_empty())
+        .collect();
+    let (new_projects, existing): (Vec<_>, Vec<_>) = project_paths
         .into_iter()
         .map(|project_path| {
             let canonical_path = project_path
                 .canonicalize()
                 .unwrap_or_else(|_| project_path.clone());
-            let exists = service.project_exists(&canonical_path).unwrap_or(false);
+            let exists = service.project_exists(&canonical_path).unwrap_or(false)
+                || workspaces.iter().any(|workspace| workspace.has_member(&canonical_path));
             (canonical_path, exists)
         })
         .partition(|(_, exists)| !exists);
@@ -71,57 +104,78 @@ pub fn handle_scan_directory(path: &Path) -> CommandResult<ScanResult> {
                 true => Ok(found_projects.into()),
                 false => {
                     info!("Adding {} to the project registry", found_projects.len());
-                    service.add_projects(found_projects.clone())?;
-                    info!("Added {} projects!", found_projects.len());
-                    Ok(found_projects.into())
+                    let added = service.add_projects_with_members(found_projects)?;
+                    info!("Added {} projects!", added.len());
+                    Ok(added.into())
                 }
             }
         }
     }
 }
 
-fn collect_project_paths(path: &Path) -> CommandResult<Vec<std::path::PathBuf>> {
+/// Re-reads the members of tracked workspaces among `project_paths`, folding their member crates
+/// into them. Registries written before members were tracked have every crate as a project.
+fn refresh_tracked_workspaces<P: ProjectRepository + ?Sized, W: WatcherRepository + ?Sized>(
+    service: &ProjectService<P, W>,
+    project_paths: &[PathBuf],
+) -> CommandResult<()> {
+    let refreshed: Vec<RustProject> = project_paths
+        .iter()
+        .filter_map(|project_path| {
+            let canonical_path = project_path.canonicalize().ok()?;
+            service.find_project_by_path(&canonical_path).ok().flatten()
+        })
+        .filter(RustProject::is_workspace)
+        .filter_map(|workspace| {
+            let members = read_workspace_members(&workspace.path);
+            (members != workspace.members).then_some(RustProject { members, ..workspace })
+        })
+        .collect();
+
+    if !refreshed.is_empty() {
+        info!("Refreshing the members of {} workspaces", refreshed.len());
+        service.add_projects_with_members(refreshed)?;
+    }
+    Ok(())
+}
+
+/// Finds every directory below `path` containing a `Cargo.toml` that `filter` allows, never
+/// descending into `excluded_paths`.
+pub fn collect_project_paths(
+    path: &Path,
+    filter: &WatchFilter,
+    excluded_paths: &[PathBuf],
+) -> CommandResult<Vec<PathBuf>> {
     let (sender, receiver) = mpsc::channel();
     let path = path.to_path_buf();
+    let excluded_paths = excluded_paths.to_vec();
+    let filter = Arc::new(filter.clone());
+    let entry_filter = Arc::clone(&filter);
     let sender_clone = sender.clone();
 
     let handle = thread::spawn(move || {
         WalkBuilder::new(&path)
-            .max_depth(Some(10))
+            .max_depth(Some(filter.remaining_depth(&path)))
+            .follow_links(filter.follow_symlinks())
             .standard_filters(false)
             .git_ignore(true)
             .git_global(false)
             .git_exclude(false)
             .hidden(false)
-            .threads(
-                std::thread::available_parallelism()
-                    .map(|n| n.get() * 2)
-                    .unwrap_or(8),
-            )
-            .filter_entry(|entry| {
-                let file_name = entry.file_name().to_string_lossy();
-
+            .threads(ScanSettings::current().scan_threads())
+            .filter_entry(move |entry| {
                 match entry.file_type().is_some_and(|ft| ft.is_dir()) {
-                    true => !matches!(
-                        file_name.as_ref(),
-                        "node_modules"
-                            | ".git"
-                            | ".svn"
-                            | "__pycache__"
-                            | ".vscode"
-                            | ".idea"
-                            | ".venv"
-                            | "build"
-                            | "dist"
-                            | "out"
-                            | "target"
-                    ),
+                    true => {
+                        !entry_filter.is_excluded_dir(entry.path())
+                            && !excluded_paths.iter().any(|excluded| entry.path().starts_with(excluded))
+                    }
                     false => true,
                 }
             })
             .build_parallel()
             .run(|| {
                 let sender = sender_clone.clone();
+                let filter = Arc::clone(&filter);
                 Box::new(move |result| {
                     result
                         .ok()
@@ -130,6 +184,7 @@ fn collect_project_paths(path: &Path) -> CommandResult<Vec<std::path::PathBuf>>
                                 && entry.file_name() == "Cargo.toml"
                         })
                         .and_then(|entry| entry.path().parent().map(|p| p.to_path_buf()))
+                        .filter(|project_path| filter.allows_project(project_path))
                         .map(|project_path| sender.send(project_path).ok());
                     ignore::WalkState::Continue
                 })
@@ -143,7 +198,7 @@ fn collect_project_paths(path: &Path) -> CommandResult<Vec<std::path::PathBuf>>
     Ok(paths)
 }
 
-fn create_rust_project_from_cargo_toml(path: &std::path::PathBuf) -> CommandResult<RustProject> {
+pub fn create_rust_project_from_cargo_toml(path: &std::path::PathBuf) -> CommandResult<RustProject> {
     determine_project_type(&path.join("Cargo.toml")).and_then(|project_type| match project_type {
         ProjectType::Package | ProjectType::WorkspaceWithPackage => {
             create_package_project(path, project_type)
@@ -153,7 +208,22 @@ fn create_rust_project_from_cargo_toml(path: &std::path::PathBuf) -> CommandResu
     })
 }
 
+/// Runs on a pool of `thread_count` threads when the config sets one, else on rayon's global pool.
 fn process_projects_parallel(paths: &[std::path::PathBuf]) -> Vec<RustProject> {
+    let pool = ScanSettings::current().threads.and_then(|threads| {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .inspect_err(|e| warn!("Processing projects on the global thread pool: {e}"))
+            .ok()
+    });
+    pool.map_or_else(
+        || process_projects(paths),
+        |pool| pool.install(|| process_projects(paths)),
+    )
+}
+
+fn process_projects(paths: &[std::path::PathBuf]) -> Vec<RustProject> {
     use std::sync::Arc;
     use std::sync::atomic::{AtomicUsize, Ordering};
 
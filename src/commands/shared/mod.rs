@@ -1,8 +1,14 @@
 pub mod rust_project_parser;
+pub mod scan_settings;
 pub mod size_calculator;
 pub mod timing;
+pub mod watch_filter;
+pub mod watcher_updates;
 
 // Re-export commonly used functions
-pub use rust_project_parser::{create_package_project, create_workspace_project, create_malformed_project};
-pub use size_calculator::{calculate_project_size, calculate_target_size, calculate_directory_size, calculate_target_directory_size};
-pub use timing::{estimate_build_time, clear_timing_cache};
\ No newline at end of file
+pub use rust_project_parser::{create_package_project, create_workspace_project, create_malformed_project, read_workspace_members};
+pub use scan_settings::ScanSettings;
+pub use size_calculator::{calculate_project_size, calculate_target_size, calculate_directory_size, calculate_target_directory_size, collect_file_sizes};
+pub use timing::{estimate_build_time, clear_timing_cache};
+pub use watch_filter::{WatchFilter, covering_watcher};
+pub use watcher_updates::{save_watcher, remove_watcher, rename_watcher, remove_all_watchers, import_watchers};
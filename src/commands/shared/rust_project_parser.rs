@@ -1,7 +1,7 @@
 use super::super::CommandResult;
 use super::{calculate_project_size, calculate_target_size, estimate_build_time};
 use crate::types::*;
-use cargo_metadata::MetadataCommand;
+use cargo_metadata::{Metadata, MetadataCommand};
 use chrono::Utc;
 use std::convert::Into;
 use std::fs;
@@ -38,6 +38,8 @@ pub fn create_package_project(
                             dependencies_count: DependencyCount::new(package.dependencies.len()),
                             estimated_build_time_seconds: estimate_build_time(path),
                             project_type,
+                            annotations: ProjectAnnotations::default(),
+                            members: workspace_members(&metadata, path),
                         })
                 })
         })
@@ -65,6 +67,8 @@ pub fn create_workspace_project(path: &PathBuf) -> CommandResult<RustProject> {
             dependencies_count: DependencyCount::new(0),
             estimated_build_time_seconds: estimate_build_time(path),
             project_type: ProjectType::PureWorkspace,
+            annotations: ProjectAnnotations::default(),
+            members: read_workspace_members(path),
         })
 }
 
@@ -90,9 +94,44 @@ pub fn create_malformed_project(path: &PathBuf) -> RustProject {
         dependencies_count: DependencyCount::new(0),
         estimated_build_time_seconds: TimingDuration::new(0),
         project_type: ProjectType::Malformed,
+        annotations: ProjectAnnotations::default(),
+        members: Vec::new(),
     }
 }
 
+/// Reads the members of the workspace at `path` again, none when cargo cannot load it.
+pub fn read_workspace_members(path: &Path) -> Vec<WorkspaceMember> {
+    MetadataCommand::new()
+        .manifest_path(path.join("Cargo.toml"))
+        .no_deps()
+        .exec()
+        .map_or_else(|_| Vec::new(), |metadata| workspace_members(&metadata, path))
+}
+
+/// The crates of the workspace rooted at `path` other than its root package, none when `path` is
+/// not a workspace root.
+fn workspace_members(metadata: &Metadata, path: &Path) -> Vec<WorkspaceMember> {
+    if metadata.workspace_root.as_std_path() != path {
+        return Vec::new();
+    }
+
+    let mut members: Vec<WorkspaceMember> = metadata
+        .workspace_packages()
+        .into_iter()
+        .filter_map(|package| {
+            let member_path = package.manifest_path.parent()?.as_std_path().to_path_buf();
+            (member_path != path).then(|| WorkspaceMember {
+                name: ProjectName::new(package.name.to_string()),
+                size_bytes: FileSize::new(calculate_project_size(&member_path)),
+                version: ProjectVersion::new(package.version.to_string()),
+                path: member_path,
+            })
+        })
+        .collect();
+    members.sort_by(|a, b| a.path.cmp(&b.path));
+    members
+}
+
 /// [workspace.package]
 fn extract_workspace_name(cargo_toml_path: &Path) -> Option<String> {
     MetadataCommand::new()
@@ -0,0 +1,108 @@
+use crate::services::{AppConfig, create_default_config_service};
+use crate::types::ProjectsResult;
+use log::warn;
+use std::sync::{Arc, PoisonError, RwLock};
+
+static CURRENT: RwLock<Option<Arc<ScanSettings>>> = RwLock::new(None);
+
+/// The `config.ron` settings that drive scanning, size calculation and build time estimates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanSettings {
+    /// Scan depth for watchers that do not set `max_depth`, `usize::MAX` when unlimited
+    pub max_depth: usize,
+    /// Threads per directory walk, `None` to size the pool automatically
+    pub threads: Option<usize>,
+    pub cache_build_times: bool,
+    /// Directory names never searched for projects
+    pub ignored_dirs: Vec<String>,
+}
+
+impl ScanSettings {
+    pub fn from_config(config: &AppConfig) -> Self {
+        let mut ignored_dirs = config.ignored_dirs.clone();
+        if config.ignore_target_dirs && !ignored_dirs.iter().any(|name| name == "target") {
+            ignored_dirs.push("target".to_string());
+        }
+
+        Self {
+            max_depth: config.max_scan_depth.unwrap_or(usize::MAX),
+            threads: config.thread_count.filter(|&threads| threads > 0),
+            cache_build_times: config.cache_build_times,
+            ignored_dirs,
+        }
+    }
+
+    /// The settings of the active config, read on first use and again by `reload`.
+    pub fn current() -> Arc<Self> {
+        let current = CURRENT.read().unwrap_or_else(PoisonError::into_inner).clone();
+        current.unwrap_or_else(|| {
+            Self::reload().unwrap_or_else(|e| {
+                warn!("Scanning with the default settings, the config could not be read: {e}");
+                Self::replace(Self::default())
+            })
+        })
+    }
+
+    /// Reads the active config again, for a long running daemon. Keeps the settings in use when
+    /// the config cannot be read.
+    pub fn reload() -> ProjectsResult<Arc<Self>> {
+        create_default_config_service()
+            .and_then(|service| service.get_config())
+            .map(|config| Self::replace(Self::from_config(&config)))
+    }
+
+    fn replace(settings: Self) -> Arc<Self> {
+        let settings = Arc::new(settings);
+        *CURRENT.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::clone(&settings));
+        settings
+    }
+
+    /// Threads for walks that find projects, which are I/O bound and wait on many directories.
+    pub fn scan_threads(&self) -> usize {
+        self.threads.unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(8, |n| n.get() * 2)
+        })
+    }
+
+    /// Threads for walks that add up file sizes, 0 lets the `ignore` crate choose.
+    pub fn size_threads(&self) -> usize {
+        self.threads.unwrap_or(0)
+    }
+}
+
+impl Default for ScanSettings {
+    fn default() -> Self {
+        Self::from_config(&AppConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::WatchFilter;
+    use std::path::Path;
+
+    #[test]
+    fn test_settings_follow_the_config() {
+        let config = AppConfig {
+            max_scan_depth: None,
+            thread_count: Some(0),
+            ignore_target_dirs: false,
+            ignored_dirs: vec!["vendor".to_string()],
+            ..AppConfig::default()
+        };
+        let settings = ScanSettings::from_config(&config);
+        assert_eq!(settings.max_depth, usize::MAX);
+        assert_eq!(settings.threads, None);
+
+        let root = Path::new("/work");
+        let filter = WatchFilter::default_for(root, &settings);
+        assert!(filter.is_excluded_dir(&root.join("game/vendor")));
+        assert!(!filter.is_excluded_dir(&root.join("game/target")));
+        assert!(!filter.is_excluded_dir(&root.join("game/node_modules")));
+
+        let defaults = ScanSettings::default();
+        assert_eq!(defaults.max_depth, 10);
+        assert!(defaults.ignored_dirs.iter().any(|name| name == "target"));
+    }
+}
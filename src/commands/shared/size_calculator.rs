@@ -1,3 +1,4 @@
+use super::ScanSettings;
 use std::path::Path;
 
 pub fn calculate_directory_size(path: &std::path::PathBuf) -> u64 {
@@ -18,7 +19,7 @@ pub fn calculate_project_size(project_path: &std::path::PathBuf) -> u64 {
         .standard_filters(false)
         .git_ignore(false)  // For size calculation, we want all files
         .hidden(false)
-        // Use default thread count from ignore crate (automatic scaling)
+        .threads(ScanSettings::current().size_threads())
         .build_parallel()
         .run(|| {
             let total_size = total_size.clone();
@@ -52,7 +53,7 @@ pub fn calculate_target_size(project_path: &Path) -> u64 {
         .standard_filters(false)
         .git_ignore(false)  // For target size, we want all files
         .hidden(false)
-        // Use default thread count from ignore crate (automatic scaling)
+        .threads(ScanSettings::current().size_threads())
         .build_parallel()
         .run(|| {
             let total_size = total_size.clone();
@@ -69,4 +70,34 @@ pub fn calculate_target_size(project_path: &Path) -> u64 {
         });
     
     total_size.load(Ordering::Relaxed)
-}
\ No newline at end of file
+}
+
+/// Size of every file below `path`, walked the same way as `calculate_project_size`.
+pub fn collect_file_sizes(path: &Path) -> Vec<(std::path::PathBuf, u64)> {
+    use ignore::WalkBuilder;
+    use std::sync::mpsc;
+
+    let (sender, receiver) = mpsc::channel();
+
+    WalkBuilder::new(path)
+        .standard_filters(false)
+        .git_ignore(false)
+        .hidden(false)
+        .threads(ScanSettings::current().size_threads())
+        .build_parallel()
+        .run(|| {
+            let sender = sender.clone();
+            Box::new(move |result| {
+                if let Ok(entry) = result
+                    && entry.file_type().is_some_and(|ft| ft.is_file())
+                    && let Ok(metadata) = entry.metadata()
+                {
+                    sender.send((entry.into_path(), metadata.len())).ok();
+                }
+                ignore::WalkState::Continue
+            })
+        });
+
+    drop(sender);
+    receiver.into_iter().collect()
+}
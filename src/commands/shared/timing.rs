@@ -1,3 +1,4 @@
+use super::ScanSettings;
 use crate::types::*;
 use std::{
     fs,
@@ -19,8 +20,10 @@ pub fn estimate_build_time(path: &PathBuf) -> TimingDuration {
 }
 
 fn estimate_build_time_impl(project_path: &PathBuf) -> ProjectsResult<u32> {
+    let cache_build_times = ScanSettings::current().cache_build_times;
+
     // Check if we have existing timing data
-    if let Ok(cached_time) = get_cached_build_time(project_path) {
+    if cache_build_times && let Ok(cached_time) = get_cached_build_time(project_path) {
         return Ok(cached_time);
     }
     
@@ -28,7 +31,9 @@ fn estimate_build_time_impl(project_path: &PathBuf) -> ProjectsResult<u32> {
     let time = run_cargo_timings_estimate(project_path)?;
     
     // Cache the result
-    let _ = cache_build_time(project_path, time);
+    if cache_build_times {
+        let _ = cache_build_time(project_path, time);
+    }
     Ok(time)
 }
 
@@ -0,0 +1,179 @@
+use super::ScanSettings;
+use crate::types::*;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::{Path, PathBuf};
+
+/// A watcher's [`WatchRules`] compiled against its path, applied by `scan` and the daemon alike.
+///
+/// Patterns are matched against paths relative to the watcher. Like `.gitignore`, a pattern without
+/// a `/` matches at any depth, so `vendor` skips every `vendor/` directory below the watcher.
+#[derive(Debug, Clone)]
+pub struct WatchFilter {
+    root: PathBuf,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    max_depth: usize,
+    follow_symlinks: bool,
+    ignored_dirs: Vec<String>,
+}
+
+impl WatchFilter {
+    /// Rules the watcher leaves out fall back to `settings`.
+    pub fn new(root: &Path, rules: &WatchRules, settings: &ScanSettings) -> ProjectsResult<Self> {
+        let include = if rules.include.is_empty() {
+            None
+        } else {
+            Some(build_glob_set(&rules.include)?)
+        };
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            include,
+            exclude: build_glob_set(&rules.exclude)?,
+            max_depth: rules.max_depth.unwrap_or(settings.max_depth),
+            follow_symlinks: rules.follow_symlinks,
+            ignored_dirs: settings.ignored_dirs.clone(),
+        })
+    }
+
+    /// The configured ignore list and depth only, for paths no watcher covers.
+    pub fn default_for(root: &Path, settings: &ScanSettings) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            include: None,
+            exclude: GlobSet::empty(),
+            max_depth: settings.max_depth,
+            follow_symlinks: false,
+            ignored_dirs: settings.ignored_dirs.clone(),
+        }
+    }
+
+    pub const fn follow_symlinks(&self) -> bool {
+        self.follow_symlinks
+    }
+
+    /// Walk depth left below `directory` so that manifests stay within the watcher's limit.
+    pub fn remaining_depth(&self, directory: &Path) -> usize {
+        let depth = self
+            .relative(directory)
+            .map_or(0, |relative| relative.components().count());
+        self.max_depth.saturating_sub(depth)
+    }
+
+    /// Directories never descended into: the configured ignore list and the `exclude` globs.
+    pub fn is_excluded_dir(&self, directory: &Path) -> bool {
+        directory
+            .file_name()
+            .is_some_and(|name| self.ignored_dirs.iter().any(|ignored| name == ignored.as_str()))
+            || self
+                .relative(directory)
+                .is_some_and(|relative| self.exclude.is_match(relative))
+    }
+
+    /// Whether a `Cargo.toml` directly inside `project_path` should be tracked under this watcher.
+    pub fn allows_project(&self, project_path: &Path) -> bool {
+        let Some(relative) = self.relative(project_path) else {
+            return true;
+        };
+
+        relative.components().count() < self.max_depth
+            && self
+                .include
+                .as_ref()
+                .is_none_or(|include| include.is_match(relative))
+            && !project_path
+                .ancestors()
+                .take_while(|ancestor| *ancestor != self.root)
+                .any(|directory| {
+                    self.is_excluded_dir(directory)
+                        || (!self.follow_symlinks && directory.is_symlink())
+                })
+    }
+
+    fn relative<'a>(&self, path: &'a Path) -> Option<&'a Path> {
+        path.strip_prefix(&self.root).ok()
+    }
+}
+
+/// The watcher with the longest path containing `path`.
+pub fn covering_watcher<'a>(
+    watchers: impl IntoIterator<Item = &'a WatcherConfig>,
+    path: &Path,
+) -> Option<&'a WatcherConfig> {
+    watchers
+        .into_iter()
+        .filter(|watcher| path.starts_with(&watcher.path))
+        .max_by_key(|watcher| watcher.path.components().count())
+}
+
+fn build_glob_set(patterns: &[String]) -> ProjectsResult<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let trimmed = pattern.trim_end_matches('/');
+        let anchored = if trimmed.contains('/') {
+            trimmed.trim_start_matches('/').to_string()
+        } else {
+            format!("**/{trimmed}")
+        };
+
+        let glob = GlobBuilder::new(&anchored)
+            .literal_separator(true)
+            .build()
+            .map_err(|source| ProjectsError::InvalidGlob {
+                pattern: pattern.clone(),
+                source,
+            })?;
+        builder.add(glob);
+    }
+
+    builder
+        .build()
+        .map_err(|source| ProjectsError::InvalidGlob {
+            pattern: patterns.join(", "),
+            source,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allows_project_honors_rules() {
+        let root = Path::new("/home/alice/work");
+        let filter = WatchFilter::new(
+            root,
+            &WatchRules {
+                include: vec!["games/*".to_string(), "tools/**".to_string()],
+                exclude: vec!["vendor/".to_string(), "fixtures".to_string()],
+                max_depth: Some(4),
+                follow_symlinks: true,
+            },
+            &ScanSettings::default(),
+        )
+        .unwrap();
+
+        assert!(filter.allows_project(&root.join("games/bevy-jam")));
+        assert!(filter.allows_project(&root.join("tools/cli/xtask")));
+        assert!(!filter.allows_project(&root.join("scratch/hello")));
+        assert!(!filter.allows_project(&root.join("tools/vendor/serde")));
+        assert!(!filter.allows_project(&root.join("tools/cli/fixtures/broken")));
+        assert!(!filter.allows_project(&root.join("tools/cli/xtask/nested")));
+        assert!(filter.is_excluded_dir(&root.join("games/vendor")));
+        assert!(filter.is_excluded_dir(&root.join("games/target")));
+        assert_eq!(filter.remaining_depth(&root.join("tools")), 3);
+    }
+
+    #[test]
+    fn test_invalid_glob_is_rejected() {
+        let rules = WatchRules {
+            exclude: vec!["[vendor".to_string()],
+            ..WatchRules::default()
+        };
+
+        assert!(matches!(
+            WatchFilter::new(Path::new("/work"), &rules, &ScanSettings::default()),
+            Err(ProjectsError::InvalidGlob { .. })
+        ));
+    }
+}
@@ -0,0 +1,60 @@
+use super::super::CommandResult;
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::services::create_default_watcher_service;
+use crate::types::*;
+
+// Watcher changes go through a running daemon so it starts or stops watching right away instead of
+// having `watchers.ron` rewritten behind its back. Without a daemon they are written directly.
+
+pub fn save_watcher(watcher: WatcherConfig) -> CommandResult {
+    match DaemonClient::connect()? {
+        Some(client) => client
+            .send(&DaemonRequest::SaveWatcher(watcher))
+            .map(|_| ()),
+        None => create_default_watcher_service()?.add_watcher(watcher),
+    }
+}
+
+pub fn remove_watcher(name: &WatcherName) -> CommandResult<bool> {
+    match DaemonClient::connect()? {
+        Some(client) => client
+            .send(&DaemonRequest::RemoveWatcher(name.clone()))
+            .map(|response| removed_count(&response) > 0),
+        None => create_default_watcher_service()?.remove_watcher(name),
+    }
+}
+
+/// Replaces the watcher called `name` with `watcher`, `false` if there is no such watcher.
+pub fn rename_watcher(name: &WatcherName, watcher: WatcherConfig) -> CommandResult<bool> {
+    match DaemonClient::connect()? {
+        Some(client) => client
+            .send(&DaemonRequest::RenameWatcher(name.clone(), Box::new(watcher)))
+            .map(|response| removed_count(&response) > 0),
+        None => create_default_watcher_service()?.rename_watcher(name, watcher),
+    }
+}
+
+pub fn remove_all_watchers() -> CommandResult<usize> {
+    match DaemonClient::connect()? {
+        Some(client) => client
+            .send(&DaemonRequest::RemoveAllWatchers)
+            .map(|response| removed_count(&response)),
+        None => create_default_watcher_service()?.remove_all_watchers(),
+    }
+}
+
+/// Imported watchers are written in one go, then a running daemon is told to pick them up.
+pub fn import_watchers(watchers: Vec<WatcherConfig>, mode: ImportMode) -> CommandResult {
+    create_default_watcher_service()?.import_watchers(watchers, mode)?;
+    if let Some(client) = DaemonClient::connect()? {
+        client.send(&DaemonRequest::Reload)?;
+    }
+    Ok(())
+}
+
+const fn removed_count(response: &DaemonResponse) -> usize {
+    match response {
+        DaemonResponse::Removed(count) => *count,
+        _ => 0,
+    }
+}
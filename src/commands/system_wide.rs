@@ -0,0 +1,185 @@
+use super::scan::{collect_project_paths, track_project_paths};
+use super::{CommandResult, ScanSettings, WatchFilter, remove_watcher, save_watcher};
+use crate::services::{
+    create_cached_project_service, create_default_config_service, create_default_watcher_service,
+};
+use crate::types::*;
+use chrono::Utc;
+use log::info;
+use std::{
+    collections::{BTreeSet, HashSet},
+    env,
+    path::{Path, PathBuf},
+};
+
+pub struct SystemWideResult {
+    pub watchers: Vec<WatcherConfig>,
+    pub removed_watchers: usize,
+    pub added_projects: usize,
+}
+
+/// Discovers every project below the configured roots (the home directory by default) and replaces
+/// the system-managed watchers with one watcher per top-level directory that contains projects.
+pub fn handle_watch_system_wide() -> CommandResult<SystemWideResult> {
+    let home = dirs::home_dir().ok_or(ProjectsError::HomeDirectoryNotFound)?;
+    let configured_roots = create_default_config_service()?
+        .get_config()?
+        .system_wide_roots;
+    let roots: Vec<PathBuf> = if configured_roots.is_empty() {
+        vec![home.clone()]
+    } else {
+        configured_roots
+    }
+    .into_iter()
+    .map(|root| root.canonicalize().unwrap_or(root))
+    .collect();
+    let excluded = toolchain_cache_dirs(&home);
+
+    let discovered = roots
+        .iter()
+        .map(|root| {
+            info!("Discovering Rust projects in: {}", root.display());
+            let filter = WatchFilter::default_for(root, &ScanSettings::current());
+            collect_project_paths(root, &filter, &excluded).map(|paths| {
+                paths
+                    .into_iter()
+                    .map(|path| (root.clone(), path))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect::<CommandResult<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+    let watcher_paths: BTreeSet<PathBuf> = discovered
+        .iter()
+        .filter_map(|(root, project_path)| watcher_root(root, project_path))
+        .collect();
+
+    let (managed, user_defined): (Vec<_>, Vec<_>) = create_default_watcher_service()?
+        .get_all_watchers()?
+        .into_iter()
+        .partition(|watcher| watcher.system_managed);
+
+    let removed_watchers = managed
+        .iter()
+        .map(|watcher| remove_watcher(&watcher.name))
+        .collect::<CommandResult<Vec<_>>>()?
+        .into_iter()
+        .filter(|removed| *removed)
+        .count();
+
+    let mut taken_names: HashSet<String> = user_defined
+        .iter()
+        .map(|watcher| watcher.name.to_string())
+        .collect();
+    let watchers: Vec<WatcherConfig> = watcher_paths
+        .into_iter()
+        .filter(|path| {
+            !user_defined
+                .iter()
+                .any(|watcher| path.starts_with(&watcher.path))
+        })
+        .map(|path| WatcherConfig {
+            name: managed_watcher_name(&mut taken_names, &path),
+            path,
+            created_at: Utc::now(),
+            system_managed: true,
+            rules: WatchRules::default(),
+            mode: WatchMode::Native,
+            paused: false,
+            health: WatcherHealth::default(),
+        })
+        .collect();
+
+    watchers
+        .iter()
+        .try_for_each(|watcher| save_watcher(watcher.clone()))?;
+
+    let service = create_cached_project_service()?;
+    let scan_result = track_project_paths(
+        &service,
+        discovered.into_iter().map(|(_, path)| path).collect(),
+    )?;
+    service.flush()?;
+
+    Ok(SystemWideResult {
+        watchers,
+        removed_watchers,
+        added_projects: scan_result.added_count,
+    })
+}
+
+/// Toolchain and cache directories full of downloaded crates that are not the user's projects.
+fn toolchain_cache_dirs(home: &Path) -> Vec<PathBuf> {
+    let cargo_home = env::var_os("CARGO_HOME").map_or_else(|| home.join(".cargo"), PathBuf::from);
+    let rustup_home =
+        env::var_os("RUSTUP_HOME").map_or_else(|| home.join(".rustup"), PathBuf::from);
+
+    [
+        cargo_home.join("registry"),
+        cargo_home.join("git"),
+        rustup_home,
+        home.join(".cache"),
+    ]
+    .into_iter()
+    .map(|path| path.canonicalize().unwrap_or(path))
+    .collect()
+}
+
+/// The direct child of `root` that contains the project, or `root` itself for a project at the root.
+fn watcher_root(root: &Path, project_path: &Path) -> Option<PathBuf> {
+    project_path.strip_prefix(root).ok().map(|relative| {
+        relative
+            .components()
+            .next()
+            .map_or_else(|| root.to_path_buf(), |first| root.join(first))
+    })
+}
+
+fn managed_watcher_name(taken_names: &mut HashSet<String>, path: &Path) -> WatcherName {
+    let base = format!(
+        "system-{}",
+        path.file_name()
+            .map_or_else(|| "root".into(), |name| name.to_string_lossy())
+    );
+    let name = std::iter::once(base.clone())
+        .chain((2..=taken_names.len() + 1).map(|n| format!("{base}-{n}")))
+        .find(|candidate| !taken_names.contains(candidate))
+        .unwrap_or(base);
+
+    taken_names.insert(name.clone());
+    WatcherName::new(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watcher_root() {
+        let root = Path::new("/home/alice");
+
+        assert_eq!(
+            watcher_root(root, Path::new("/home/alice/dev/game/crates/render")),
+            Some(PathBuf::from("/home/alice/dev"))
+        );
+        assert_eq!(watcher_root(root, root), Some(root.to_path_buf()));
+        assert_eq!(watcher_root(root, Path::new("/opt/project")), None);
+    }
+
+    #[test]
+    fn test_managed_watcher_name_avoids_taken_names() {
+        let mut taken = HashSet::from(["system-dev".to_string()]);
+
+        assert_eq!(
+            managed_watcher_name(&mut taken, Path::new("/home/alice/dev")).as_str(),
+            "system-dev-2"
+        );
+        assert_eq!(
+            managed_watcher_name(&mut taken, Path::new("/mnt/dev")).as_str(),
+            "system-dev-3"
+        );
+    }
+}
@@ -0,0 +1,16 @@
+use super::CommandResult;
+use crate::services::create_default_project_service;
+use crate::types::*;
+
+/// Adds and removes tags of a project in the given order, returning the tagged project.
+pub fn handle_tag_project(project: &ProjectRef, edits: &[TagEdit]) -> CommandResult<RustProject> {
+    let service = create_default_project_service()?;
+    let id = service.resolve_project(project)?.id;
+    service
+        .annotate_project(id, &|annotations| {
+            for edit in edits {
+                edit.apply(&mut annotations.tags);
+            }
+        })?
+        .ok_or(ProjectsError::ProjectNotFound { id })
+}
@@ -1,7 +1,8 @@
 use super::{
     CommandResult, calculate_directory_size, calculate_target_directory_size, estimate_build_time,
+    read_workspace_members,
 };
-use crate::services::create_default_project_service;
+use crate::services::create_cached_project_service;
 use crate::types::*;
 
 pub struct UpdateResult {
@@ -18,17 +19,20 @@ impl From<Vec<String>> for UpdateResult {
     }
 }
 
+/// Re-measures every project and saves the changed ones in one write.
 pub fn handle_update_projects() -> CommandResult<UpdateResult> {
-    let service = create_default_project_service()?;
+    let service = create_cached_project_service()?;
 
-    service
+    let (projects, names): (Vec<RustProject>, Vec<String>) = service
         .get_all_projects()?
         .into_iter()
-        .map(|project| update_project_metrics(project))
+        .map(update_project_metrics)
         .filter_map(|(updated, project, name)| updated.then_some((project, name)))
-        .map(|(project, name)| service.add_project(project).map(|_| name))
-        .collect::<Result<Vec<_>, _>>()
-        .map(Into::into)
+        .unzip();
+
+    service.add_projects_with_members(projects)?;
+    service.flush()?;
+    Ok(names.into())
 }
 
 
@@ -49,16 +53,25 @@ fn update_project_metrics(project: RustProject) -> (bool, RustProject, String) {
         _ => new_build_time,
     };
 
+    let new_members = if project.is_workspace() {
+        read_workspace_members(&project.path)
+    } else {
+        Vec::new()
+    };
+    let members_changed = new_members != project.members;
+
     let updated_project = RustProject {
         size_bytes: new_size,
         target_size_bytes: new_target_size,
         estimated_build_time_seconds: updated_build_time,
+        members: new_members,
         ..project
     };
 
     let has_changes = old_metrics.0.bytes() != new_size.bytes()
         || old_metrics.1.bytes() != new_target_size.bytes()
-        || old_metrics.2.seconds() != updated_build_time.seconds();
+        || old_metrics.2.seconds() != updated_build_time.seconds()
+        || members_changed;
 
     (has_changes, updated_project, name)
 }
@@ -1,19 +1,206 @@
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
 use crate::types::*;
 use crate::services::create_default_watcher_service;
-use super::CommandResult;
+use super::{CommandResult, ScanSettings, WatchFilter, handle_list_projects, list::ProjectListResult, remove_watcher, rename_watcher, save_watcher};
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 pub struct WatcherListResult {
     pub watchers: Vec<WatcherConfig>,
+    /// Watchers the running daemon is watching, `None` when no daemon is running
+    pub active: Option<HashSet<WatcherName>>,
 }
 
-impl From<Vec<WatcherConfig>> for WatcherListResult {
-    fn from(watchers: Vec<WatcherConfig>) -> Self {
-        Self { watchers }
-    }
+pub struct WatcherDetails {
+    pub watcher: WatcherConfig,
+    pub projects: ProjectListResult,
+    pub total_size: FileSize,
+    pub target_size: FileSize,
+}
+
+/// Changes requested by `watchers edit`; `None` keeps the current value.
+pub struct WatcherEdit {
+    pub path: Option<PathBuf>,
+    pub include: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+    pub max_depth: Option<usize>,
+    pub follow_symlinks: Option<bool>,
+    pub mode: Option<WatchMode>,
 }
 
+/// Lists watchers with the daemon's in-memory health when it is running, which is newer than the
+/// health it last wrote to the registry.
 pub fn handle_list_watchers() ->
   CommandResult<WatcherListResult> {
-      create_default_watcher_service()?.get_all_watchers().map(Into::into)
+      let mut watchers = create_default_watcher_service()?.get_all_watchers()?;
+      let daemon_watchers = DaemonClient::connect()?
+          .and_then(|client| client.send(&DaemonRequest::Status).ok())
+          .and_then(|response| match response {
+              DaemonResponse::Status(status) => Some(status.active_watchers),
+              _ => None,
+          });
+
+      let active = daemon_watchers.map(|active_watchers| {
+          let health: HashMap<WatcherName, WatcherHealth> = active_watchers
+              .into_iter()
+              .map(|watcher| (watcher.name, watcher.health))
+              .collect();
+          for watcher in &mut watchers {
+              if let Some(current) = health.get(&watcher.name) {
+                  watcher.health = current.clone();
+              }
+          }
+          health.into_keys().collect()
+      });
+
+      watchers.sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+      Ok(WatcherListResult { watchers, active })
+}
+
+/// Adds a watcher named after the watched directory unless a name is given.
+pub fn handle_add_watcher(
+    path: &Path,
+    name: Option<WatcherName>,
+    rules: WatchRules,
+    mode: WatchMode,
+) -> CommandResult<WatcherConfig> {
+    let path = canonical(path);
+    let name = name.unwrap_or_else(|| {
+        WatcherName::new(
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unnamed")
+                .to_string(),
+        )
+    });
+    ensure_name_available(&name)?;
+    WatchFilter::new(&path, &rules, &ScanSettings::current())?;
+
+    let watcher = WatcherConfig {
+        name,
+        path,
+        created_at: Utc::now(),
+        system_managed: false,
+        rules,
+        mode,
+        paused: false,
+        health: WatcherHealth::default(),
+    };
+    save_watcher(watcher.clone()).map(|()| watcher)
+}
+
+pub fn handle_remove_watcher(name: &WatcherName) -> CommandResult {
+    if remove_watcher(name)? {
+        Ok(())
+    } else {
+        Err(ProjectsError::WatcherNotFound { name: name.clone() })
+    }
+}
+
+// Renamed and edited watchers become user-defined so `watch --system-wide` leaves them alone.
+
+pub fn handle_rename_watcher(
+    name: &WatcherName,
+    new_name: &WatcherName,
+) -> CommandResult<WatcherConfig> {
+    let watcher = find_watcher(name)?;
+    ensure_name_available(new_name)?;
+
+    let renamed = WatcherConfig {
+        name: new_name.clone(),
+        system_managed: false,
+        ..watcher
+    };
+    if rename_watcher(name, renamed.clone())? {
+        Ok(renamed)
+    } else {
+        Err(ProjectsError::WatcherNotFound { name: name.clone() })
+    }
+}
+
+pub fn handle_edit_watcher(name: &WatcherName, edit: WatcherEdit) -> CommandResult<WatcherConfig> {
+    let watcher = find_watcher(name)?;
+    let rules = WatchRules {
+        include: edit.include.unwrap_or(watcher.rules.include),
+        exclude: edit.exclude.unwrap_or(watcher.rules.exclude),
+        max_depth: edit.max_depth.or(watcher.rules.max_depth),
+        follow_symlinks: edit.follow_symlinks.unwrap_or(watcher.rules.follow_symlinks),
+    };
+    let path = edit.path.as_deref().map_or(watcher.path, canonical);
+    WatchFilter::new(&path, &rules, &ScanSettings::current())?;
+
+    let edited = WatcherConfig {
+        path,
+        rules,
+        mode: edit.mode.unwrap_or(watcher.mode),
+        system_managed: false,
+        ..watcher
+    };
+    save_watcher(edited.clone()).map(|()| edited)
+}
+
+pub fn handle_pause_watcher(name: &WatcherName) -> CommandResult<WatcherConfig> {
+    set_paused(name, true)
+}
+
+pub fn handle_resume_watcher(name: &WatcherName) -> CommandResult<WatcherConfig> {
+    set_paused(name, false)
+}
+
+fn set_paused(name: &WatcherName, paused: bool) -> CommandResult<WatcherConfig> {
+    let watcher = WatcherConfig {
+        paused,
+        ..find_watcher(name)?
+    };
+    save_watcher(watcher.clone()).map(|()| watcher)
+}
+
+pub fn handle_show_watcher(name: &WatcherName) -> CommandResult<WatcherDetails> {
+    let watcher = find_watcher(name)?;
+    let projects = handle_list_projects(Some(name), &[])?;
+    let (total_size, target_size) = combined_sizes(&projects.projects);
+
+    Ok(WatcherDetails {
+        watcher,
+        projects,
+        total_size,
+        target_size,
+    })
+}
+
+/// Nested projects are counted once, as part of the outermost project containing them.
+fn combined_sizes(projects: &[RustProject]) -> (FileSize, FileSize) {
+    let (total, target) = projects
+        .iter()
+        .filter(|project| {
+            !projects
+                .iter()
+                .any(|other| other.path != project.path && project.path.starts_with(&other.path))
+        })
+        .fold((0, 0), |(total, target), project| {
+            (
+                total + project.size_bytes.bytes(),
+                target + project.target_size_bytes.bytes(),
+            )
+        });
+    (FileSize::new(total), FileSize::new(target))
+}
+
+fn find_watcher(name: &WatcherName) -> CommandResult<WatcherConfig> {
+    create_default_watcher_service()?
+        .get_watcher_by_name(name)?
+        .ok_or_else(|| ProjectsError::WatcherNotFound { name: name.clone() })
 }
 
+fn ensure_name_available(name: &WatcherName) -> CommandResult {
+    if create_default_watcher_service()?.watcher_exists(name)? {
+        Err(ProjectsError::WatcherAlreadyExists { name: name.clone() })
+    } else {
+        Ok(())
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
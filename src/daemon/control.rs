@@ -0,0 +1,214 @@
+use super::event_loop::DaemonMessage;
+use crate::services::default_config_dir;
+use crate::types::*;
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// One request per connection, sent as a single line of RON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DaemonRequest {
+    Status,
+    Reload,
+    Stop,
+    SaveWatcher(WatcherConfig),
+    RemoveWatcher(WatcherName),
+    /// Stores the watcher in place of the named one
+    RenameWatcher(WatcherName, Box<WatcherConfig>),
+    RemoveAllWatchers,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DaemonResponse {
+    Status(DaemonStatus),
+    Done,
+    Removed(usize),
+    Error(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    pub processed_events: usize,
+    pub active_watchers: Vec<WatcherConfig>,
+}
+
+pub fn socket_path() -> ProjectsResult<PathBuf> {
+    default_config_dir().map(|dir| dir.join("daemon.sock"))
+}
+
+pub struct DaemonClient {
+    socket_path: PathBuf,
+}
+
+impl DaemonClient {
+    /// Returns a client only if a daemon is listening on the control socket.
+    pub fn connect() -> ProjectsResult<Option<Self>> {
+        socket_path().map(|socket_path| {
+            UnixStream::connect(&socket_path)
+                .ok()
+                .map(|_| Self { socket_path })
+        })
+    }
+
+    pub fn send(&self, request: &DaemonRequest) -> ProjectsResult<DaemonResponse> {
+        let mut stream = UnixStream::connect(&self.socket_path)?;
+        stream.set_read_timeout(Some(SOCKET_TIMEOUT))?;
+        writeln!(stream, "{}", ron::to_string(request)?)?;
+
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+
+        match ron::from_str(line.trim())? {
+            DaemonResponse::Error(message) => Err(ProjectsError::Daemon { message }),
+            response => Ok(response),
+        }
+    }
+}
+
+/// Binds the control socket, replacing a socket file left behind by a daemon that did not shut down.
+pub fn bind_control_socket(socket_path: &Path) -> ProjectsResult<UnixListener> {
+    if socket_path.exists() {
+        if UnixStream::connect(socket_path).is_ok() {
+            return Err(ProjectsError::Daemon {
+                message: format!("already running on {}", socket_path.display()),
+            });
+        }
+        fs::remove_file(socket_path)?;
+    }
+
+    Ok(UnixListener::bind(socket_path)?)
+}
+
+/// Accepts control connections on a background thread and forwards them to the event loop.
+///
+/// The thread ends after answering a `Stop` request; joining it lets the daemon exit only once
+/// that answer was written.
+pub fn serve_control_socket(
+    listener: UnixListener,
+    sender: mpsc::Sender<DaemonMessage>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for stream in listener.incoming().filter_map(Result::ok) {
+            match handle_connection(&stream, &sender) {
+                Ok(true) => break,
+                Ok(false) => {}
+                Err(e) => warn!("Control connection failed: {e}"),
+            }
+        }
+    })
+}
+
+/// Returns whether the daemon was told to stop.
+fn handle_connection(
+    stream: &UnixStream,
+    sender: &mpsc::Sender<DaemonMessage>,
+) -> ProjectsResult<bool> {
+    stream.set_read_timeout(Some(SOCKET_TIMEOUT))?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    // `DaemonClient::connect` probes the socket without sending anything
+    if line.trim().is_empty() {
+        return Ok(false);
+    }
+    let request: DaemonRequest = ron::from_str(line.trim())?;
+    let stop = matches!(request, DaemonRequest::Stop);
+
+    let (reply_sender, reply_receiver) = mpsc::channel();
+    sender
+        .send(DaemonMessage::Control(request, reply_sender))
+        .map_err(|_| "Daemon event loop has stopped")?;
+    let response = reply_receiver
+        .recv()
+        .unwrap_or_else(|_| DaemonResponse::Error("Daemon event loop has stopped".to_string()));
+
+    let mut writer = stream;
+    let written = writeln!(writer, "{}", ron::to_string(&response)?);
+    // The daemon is stopping whether or not the client is still there to hear it
+    if stop {
+        return Ok(true);
+    }
+    written.map(|()| false).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_round_trips_as_single_line() {
+        let request = DaemonRequest::SaveWatcher(WatcherConfig {
+            name: WatcherName::new("dev\nprojects".to_string()),
+            path: PathBuf::from("/home/alice/dev"),
+            created_at: Utc::now(),
+            system_managed: false,
+            rules: WatchRules::default(),
+            mode: WatchMode::Native,
+            paused: false,
+            health: WatcherHealth::default(),
+        });
+
+        let line = ron::to_string(&request).unwrap();
+        assert!(!line.contains('\n'));
+
+        match ron::from_str(&line).unwrap() {
+            DaemonRequest::SaveWatcher(watcher) => {
+                assert_eq!(watcher.name.as_str(), "dev\nprojects");
+            }
+            other => panic!("unexpected request: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_requests_are_answered_over_the_socket() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let socket_path = temp_dir.path().join("daemon.sock");
+        let (sender, receiver) = mpsc::channel();
+        let control = serve_control_socket(bind_control_socket(&socket_path)?, sender);
+        // Stands in for the event loop, answering until it is told to stop
+        let event_loop = thread::spawn(move || {
+            for message in receiver {
+                let DaemonMessage::Control(request, reply) = message else {
+                    continue;
+                };
+                let stop = matches!(request, DaemonRequest::Stop);
+                let response = match request {
+                    DaemonRequest::RemoveWatcher(name) => DaemonResponse::Removed(name.as_str().len()),
+                    DaemonRequest::Reload => DaemonResponse::Error("registry is locked".to_string()),
+                    _ => DaemonResponse::Done,
+                };
+                reply.send(response).ok();
+                if stop {
+                    break;
+                }
+            }
+        });
+
+        assert!(bind_control_socket(&socket_path).is_err());
+        let client = DaemonClient { socket_path };
+        let removed = client.send(&DaemonRequest::RemoveWatcher(WatcherName::new("work".to_string())))?;
+        assert!(matches!(removed, DaemonResponse::Removed(4)));
+        match client.send(&DaemonRequest::Reload) {
+            Err(ProjectsError::Daemon { message }) => assert_eq!(message, "registry is locked"),
+            other => panic!("expected the daemon error, got {other:?}"),
+        }
+        assert!(matches!(client.send(&DaemonRequest::Stop)?, DaemonResponse::Done));
+
+        control.join().unwrap();
+        event_loop.join().unwrap();
+        Ok(())
+    }
+}
@@ -0,0 +1,997 @@
+use super::control::{
+    DaemonRequest, DaemonResponse, DaemonStatus, bind_control_socket, serve_control_socket,
+};
+use super::size_tracker::{ProjectSizes, SizeTracker};
+use crate::commands::scan::{
+    collect_project_paths, create_rust_project_from_cargo_toml, track_project_paths,
+};
+use crate::commands::{ScanSettings, WatchFilter, covering_watcher, read_workspace_members};
+use crate::repositories::{ProjectRepository, WatcherRepository};
+use crate::services::{ProjectService, WatcherService};
+use crate::types::*;
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
+use notify::{
+    ErrorKind, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode,
+    event::{ModifyKind, RenameMode},
+};
+use notify_debouncer_full::{
+    DebounceEventResult, DebouncedEvent, Debouncer, RecommendedCache, new_debouncer,
+    new_debouncer_opt,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, mpsc},
+    time::{Duration, Instant},
+};
+
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(2);
+const RECONCILE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const HEALTH_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Everything the event loop reacts to, filesystem batches and control socket requests alike.
+pub enum DaemonMessage {
+    Filesystem(DebounceEventResult),
+    Control(DaemonRequest, mpsc::Sender<DaemonResponse>),
+}
+
+pub struct DaemonSummary {
+    pub watched_paths: Vec<PathBuf>,
+    pub processed_events: usize,
+}
+
+struct Daemon<'a, P: ProjectRepository + ?Sized, W: WatcherRepository + ?Sized> {
+    service: &'a ProjectService<P, W>,
+    watcher_service: &'a WatcherService<W>,
+    debouncer: Debouncer<RecommendedWatcher, RecommendedCache>,
+    /// One polling debouncer per polled watcher, since each has its own interval
+    pollers: HashMap<PathBuf, Debouncer<PollWatcher, RecommendedCache>>,
+    sender: mpsc::Sender<DaemonMessage>,
+    fallback_poll_interval: Duration,
+    active_watchers: HashMap<PathBuf, WatcherConfig>,
+    filters: HashMap<PathBuf, WatchFilter>,
+    /// Replaced by `Reload`, so `config.ron` changes apply without a restart
+    settings: Arc<ScanSettings>,
+    sizes: SizeTracker,
+    sizes_checked_at: Instant,
+    /// Sizes saved since the last flush, recorded in the history in one write
+    pending_snapshots: Vec<(ProjectId, SizeSnapshot)>,
+    processed_events: usize,
+    started_at: DateTime<Utc>,
+    /// Watcher health changed since it was last written to the registry
+    health_dirty: bool,
+    health_flushed_at: Instant,
+}
+
+/// Watches every registered watcher path and keeps the project registry in sync.
+///
+/// Paths share one native debouncer, polled watchers get a debouncer each. Blocks until a `Stop`
+/// request arrives on the control socket.
+pub fn run_daemon<P: ProjectRepository + ?Sized, W: WatcherRepository + ?Sized>(
+    service: &ProjectService<P, W>,
+    watcher_service: &WatcherService<W>,
+    socket_path: &Path,
+    reconcile_interval: Duration,
+    fallback_poll_interval: Duration,
+) -> ProjectsResult<DaemonSummary> {
+    let listener = bind_control_socket(socket_path)?;
+    let (sender, receiver) = mpsc::channel();
+    let mut daemon = Daemon::new(
+        service,
+        watcher_service,
+        sender.clone(),
+        reconcile_interval,
+        fallback_poll_interval,
+    )?;
+    daemon.sync_watchers()?;
+    if daemon.active_watchers.is_empty() {
+        warn!("No watcher paths are being watched yet. Add one with `cargo projects watch`");
+    }
+
+    let control = serve_control_socket(listener, sender);
+    info!(
+        "Listening for control requests on {}",
+        socket_path.display()
+    );
+
+    let summary = daemon.run(&receiver);
+    fs::remove_file(socket_path).ok();
+    control.join().ok();
+    Ok(summary)
+}
+
+impl<'a, P: ProjectRepository + ?Sized, W: WatcherRepository + ?Sized> Daemon<'a, P, W> {
+    /// A daemon watching nothing yet, `sync_watchers` starts the watchers of the registry.
+    fn new(
+        service: &'a ProjectService<P, W>,
+        watcher_service: &'a WatcherService<W>,
+        sender: mpsc::Sender<DaemonMessage>,
+        reconcile_interval: Duration,
+        fallback_poll_interval: Duration,
+    ) -> ProjectsResult<Self> {
+        let debouncer = new_debouncer(DEBOUNCE_TIMEOUT, None, forward_events(sender.clone()))?;
+        Ok(Self {
+            service,
+            watcher_service,
+            debouncer,
+            pollers: HashMap::new(),
+            sender,
+            fallback_poll_interval,
+            active_watchers: HashMap::new(),
+            filters: HashMap::new(),
+            settings: ScanSettings::current(),
+            sizes: SizeTracker::new(reconcile_interval),
+            sizes_checked_at: Instant::now(),
+            pending_snapshots: Vec::new(),
+            processed_events: 0,
+            started_at: Utc::now(),
+            health_dirty: false,
+            health_flushed_at: Instant::now(),
+        })
+    }
+
+    fn run(&mut self, receiver: &mpsc::Receiver<DaemonMessage>) -> DaemonSummary {
+        loop {
+            let message = match receiver.recv_timeout(RECONCILE_CHECK_INTERVAL) {
+                Ok(message) => message,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    self.reconcile_sizes();
+                    self.flush_snapshots();
+                    self.flush_health();
+                    continue;
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            };
+
+            match message {
+                DaemonMessage::Filesystem(result) => {
+                    self.processed_events += self.handle_debounce_result(result);
+                }
+                DaemonMessage::Control(DaemonRequest::Stop, reply) => {
+                    info!("Stop requested, shutting down");
+                    self.flush_snapshots();
+                    self.flush_health();
+                    reply.send(DaemonResponse::Done).ok();
+                    break;
+                }
+                DaemonMessage::Control(request, reply) => {
+                    let response = self
+                        .handle_request(request)
+                        .unwrap_or_else(|e| DaemonResponse::Error(e.to_string()));
+                    reply.send(response).ok();
+                }
+            }
+
+            // A busy build can keep events coming for longer than the receive timeout
+            if self.sizes_checked_at.elapsed() >= RECONCILE_CHECK_INTERVAL {
+                self.reconcile_sizes();
+            }
+            if self.health_flushed_at.elapsed() >= HEALTH_FLUSH_INTERVAL {
+                self.flush_snapshots();
+                self.flush_health();
+            }
+        }
+        self.flush_snapshots();
+        self.flush_health();
+
+        DaemonSummary {
+            watched_paths: self.active_watchers.keys().cloned().collect(),
+            processed_events: self.processed_events,
+        }
+    }
+
+    fn handle_request(&mut self, request: DaemonRequest) -> ProjectsResult<DaemonResponse> {
+        match request {
+            DaemonRequest::Status => Ok(DaemonResponse::Status(self.status())),
+            DaemonRequest::Reload => {
+                self.settings = ScanSettings::reload()?;
+                self.sync_watchers().map(|()| DaemonResponse::Done)
+            }
+            // `run` answers `Stop` itself, since it has to leave the loop
+            DaemonRequest::Stop => unreachable!("Stop is handled by run"),
+            DaemonRequest::SaveWatcher(watcher) => self
+                .watcher_service
+                .add_watcher(watcher)
+                .and_then(|()| self.sync_watchers())
+                .map(|()| DaemonResponse::Done),
+            DaemonRequest::RemoveWatcher(name) => self
+                .watcher_service
+                .remove_watcher(&name)
+                .and_then(|removed| self.sync_watchers().map(|()| usize::from(removed)))
+                .map(DaemonResponse::Removed),
+            DaemonRequest::RenameWatcher(name, watcher) => self
+                .watcher_service
+                .rename_watcher(&name, *watcher)
+                .and_then(|renamed| self.sync_watchers().map(|()| usize::from(renamed)))
+                .map(DaemonResponse::Removed),
+            DaemonRequest::RemoveAllWatchers => self
+                .watcher_service
+                .remove_all_watchers()
+                .and_then(|count| self.sync_watchers().map(|()| count))
+                .map(DaemonResponse::Removed),
+        }
+    }
+
+    fn status(&self) -> DaemonStatus {
+        DaemonStatus {
+            pid: std::process::id(),
+            started_at: self.started_at,
+            processed_events: self.processed_events,
+            active_watchers: self.active_watchers.values().cloned().collect(),
+        }
+    }
+
+    /// Re-reads the watcher registry, unwatching paths that were removed or paused and watching new
+    /// ones. Health is kept from memory since the registry copy may be up to a flush behind.
+    fn sync_watchers(&mut self) -> ProjectsResult<()> {
+        // Watchers about to be dropped from memory would otherwise lose their unsaved health
+        self.flush_health();
+        let configured: HashMap<PathBuf, WatcherConfig> = self
+            .watcher_service
+            .get_all_watchers()?
+            .into_iter()
+            .filter(|watcher| !watcher.paused)
+            .map(|watcher| (watcher.path.clone(), watcher))
+            .collect();
+
+        // A changed mode needs the path watched again by the other kind of watcher
+        let stale: Vec<PathBuf> = self
+            .active_watchers
+            .iter()
+            .filter(|(path, active)| {
+                configured
+                    .get(*path)
+                    .is_none_or(|watcher| watcher.mode != active.mode)
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in stale {
+            self.stop_watching(&path);
+            self.active_watchers.remove(&path);
+            info!("Stopped watching {}", path.display());
+        }
+
+        for (path, watcher) in configured {
+            if let Some(active) = self.active_watchers.get_mut(&path) {
+                let health = active.health.clone();
+                *active = WatcherConfig { health, ..watcher };
+                continue;
+            }
+
+            match self.start_watching(&watcher) {
+                Ok(backend) => {
+                    info!("Watching {} at {} ({backend})", watcher.name, path.display());
+                    let mut watcher = watcher;
+                    watcher.health.polling = !matches!(backend, Backend::Native);
+                    watcher.health.last_error = None;
+                    watcher.health.last_error_at = None;
+                    if let Backend::FallbackPoll(e) = backend {
+                        watcher.health.last_error = Some(format!("{e} Polling instead"));
+                        watcher.health.last_error_at = Some(Utc::now());
+                    }
+                    self.health_dirty = true;
+                    self.active_watchers.insert(path, watcher);
+                }
+                Err(e) => {
+                    error!("Failed to watch {} at {}: {e}", watcher.name, path.display());
+                    let health = WatcherHealth {
+                        last_error: Some(e.to_string()),
+                        last_error_at: Some(Utc::now()),
+                        ..watcher.health
+                    };
+                    if let Err(e) = self.watcher_service.update_health(&watcher.name, health) {
+                        warn!("Failed to record watcher error: {e}");
+                    }
+                }
+            }
+        }
+
+        self.filters = self
+            .active_watchers
+            .values()
+            .map(|watcher| {
+                let settings = &self.settings;
+                let filter = WatchFilter::new(&watcher.path, &watcher.rules, settings).unwrap_or_else(|e| {
+                    warn!("Ignoring the rules of watcher {}: {e}", watcher.name);
+                    WatchFilter::default_for(&watcher.path, settings)
+                });
+                (watcher.path.clone(), filter)
+            })
+            .collect();
+
+        Ok(())
+    }
+
+    /// Native watching falls back to polling when the inotify watch limit is exhausted.
+    fn start_watching(&mut self, watcher: &WatcherConfig) -> Result<Backend, notify::Error> {
+        match watcher.mode {
+            WatchMode::Poll { interval_secs } => self
+                .start_polling(watcher, Duration::from_secs(interval_secs))
+                .map(|()| Backend::Poll),
+            WatchMode::Native => match self.debouncer.watch(&watcher.path, RecursiveMode::Recursive) {
+                Ok(()) => Ok(Backend::Native),
+                Err(e) if matches!(e.kind, ErrorKind::MaxFilesWatch) => {
+                    self.fall_back_to_polling(watcher);
+                    Ok(Backend::FallbackPoll(e))
+                }
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    fn start_polling(&mut self, watcher: &WatcherConfig, interval: Duration) -> Result<(), notify::Error> {
+        let config = notify::Config::default()
+            .with_poll_interval(interval)
+            .with_follow_symlinks(watcher.rules.follow_symlinks);
+        let mut poller = new_debouncer_opt(
+            DEBOUNCE_TIMEOUT,
+            None,
+            forward_events(self.sender.clone()),
+            RecommendedCache::new(),
+            config,
+        )?;
+        poller.watch(&watcher.path, RecursiveMode::Recursive)?;
+        self.pollers.insert(watcher.path.clone(), poller);
+        Ok(())
+    }
+
+    /// Replaces the native watch of a path, including whatever part of the tree inotify managed to
+    /// watch before running out, with a poller at the fallback interval.
+    fn fall_back_to_polling(&mut self, watcher: &WatcherConfig) {
+        warn!(
+            "Watch limit reached, polling {} every {}s instead",
+            watcher.path.display(),
+            self.fallback_poll_interval.as_secs()
+        );
+        self.debouncer.unwatch(&watcher.path).ok();
+        if let Err(e) = self.start_polling(watcher, self.fallback_poll_interval) {
+            error!("Failed to poll {}: {e}", watcher.path.display());
+        }
+    }
+
+    fn stop_watching(&mut self, path: &Path) {
+        if self.pollers.remove(path).is_none() {
+            self.debouncer
+                .unwatch(path)
+                .unwrap_or_else(|e| warn!("Failed to unwatch {}: {e}", path.display()));
+        }
+    }
+
+    /// Moves natively watched paths hit by a watch limit error at runtime over to polling.
+    fn handle_watch_limit(&mut self, error: &notify::Error) {
+        let affected: Vec<WatcherConfig> = self
+            .active_watchers
+            .values()
+            .filter(|watcher| !self.pollers.contains_key(&watcher.path))
+            .filter(|watcher| {
+                error.paths.is_empty()
+                    || error.paths.iter().any(|path| path.starts_with(&watcher.path))
+            })
+            .cloned()
+            .collect();
+
+        for watcher in affected {
+            self.fall_back_to_polling(&watcher);
+            if let Some(active) = self.active_watchers.get_mut(&watcher.path) {
+                active.health.polling = self.pollers.contains_key(&watcher.path);
+                self.health_dirty = true;
+            }
+        }
+    }
+
+    /// The innermost active watcher containing `path`.
+    fn covering_watcher_mut(&mut self, path: &Path) -> Option<&mut WatcherConfig> {
+        let watcher_path = covering_watcher(self.active_watchers.values(), path)?
+            .path
+            .clone();
+        self.active_watchers.get_mut(&watcher_path)
+    }
+
+    fn record_activity(&mut self, events: &[DebouncedEvent]) {
+        let now = Utc::now();
+        let watcher_paths: HashSet<PathBuf> = events
+            .iter()
+            .flat_map(|event| event.paths.iter())
+            .filter_map(|path| covering_watcher(self.active_watchers.values(), path))
+            .map(|watcher| watcher.path.clone())
+            .collect();
+
+        for path in &watcher_paths {
+            if let Some(watcher) = self.active_watchers.get_mut(path) {
+                watcher.health.last_event_at = Some(now);
+                self.health_dirty = true;
+            }
+        }
+    }
+
+    fn record_discovered(&mut self, path: &Path, count: usize) {
+        if count == 0 {
+            return;
+        }
+        if let Some(watcher) = self.covering_watcher_mut(path) {
+            watcher.health.projects_discovered += count;
+            self.health_dirty = true;
+        }
+    }
+
+    /// Errors without paths, like an exhausted inotify watch limit, are recorded on every watcher.
+    fn record_error(&mut self, error: &notify::Error) {
+        let message = error.to_string();
+        let now = Utc::now();
+
+        for watcher in self.active_watchers.values_mut().filter(|watcher| {
+            error.paths.is_empty() || error.paths.iter().any(|path| path.starts_with(&watcher.path))
+        }) {
+            watcher.health.last_error = Some(message.clone());
+            watcher.health.last_error_at = Some(now);
+            self.health_dirty = true;
+        }
+    }
+
+    /// Records the sizes saved since the last flush in the size history.
+    fn flush_snapshots(&mut self) {
+        let snapshots = std::mem::take(&mut self.pending_snapshots);
+        if let Err(e) = self.service.record_sizes(snapshots) {
+            warn!("Failed to record size history: {e}");
+        }
+    }
+
+    /// Writes the health of active watchers back to the registry.
+    fn flush_health(&mut self) {
+        if self.health_dirty {
+            for watcher in self.active_watchers.values() {
+                if let Err(e) = self
+                    .watcher_service
+                    .update_health(&watcher.name, watcher.health.clone())
+                {
+                    warn!("Failed to save health of {}: {e}", watcher.name);
+                }
+            }
+            self.health_dirty = false;
+        }
+        self.health_flushed_at = Instant::now();
+    }
+
+    /// An untracked `Cargo.toml` in a directory the rules of its watcher and the `ignored_dirs` of
+    /// the config allow. `target/` is skipped only as one of those `ignored_dirs`, which
+    /// `ignore_target_dirs` adds it to.
+    fn is_new_project_manifest(&self, path: &Path) -> bool {
+        is_untracked_manifest(self.service, path)
+            && path.parent().is_some_and(|project_path| {
+                self.filter_for(project_path)
+                    .is_some_and(|filter| filter.allows_project(project_path))
+            })
+    }
+
+    /// The rules of the innermost watcher containing `path`.
+    fn filter_for(&self, path: &Path) -> Option<&WatchFilter> {
+        covering_watcher(self.active_watchers.values(), path)
+            .and_then(|watcher| self.filters.get(&watcher.path))
+    }
+
+    fn handle_debounce_result(&mut self, result: DebounceEventResult) -> usize {
+        match result {
+            Ok(events) => self.handle_events(&events),
+            Err(errors) => {
+                for error in &errors {
+                    error!("Watch error: {error:?}");
+                    self.record_error(error);
+                    if matches!(error.kind, ErrorKind::MaxFilesWatch) {
+                        self.handle_watch_limit(error);
+                    }
+                }
+                self.flush_health();
+                0
+            }
+        }
+    }
+
+    /// Applies moves and deletions, registers newly created projects and updates the sizes of every
+    /// project touched by the batch with one registry write per project.
+    fn handle_events(&mut self, events: &[DebouncedEvent]) -> usize {
+        let service = self.service;
+        self.record_activity(events);
+
+        events
+            .iter()
+            .filter_map(|event| renamed_directory(event))
+            .for_each(|(from, to)| {
+                self.sizes.forget_below(from);
+                relocate_projects(service, from, to);
+            });
+
+        let removed_paths: Vec<&PathBuf> = events
+            .iter()
+            .filter(|event| is_removal(event))
+            .flat_map(|event| event.paths.iter())
+            .filter(|path| !path.exists())
+            .collect();
+        if !removed_paths.is_empty() {
+            for project_path in untrack_removed_projects(service, &removed_paths) {
+                self.sizes.forget_below(&project_path);
+            }
+        }
+
+        let moved_in: Vec<(&Path, usize)> = events
+            .iter()
+            .filter_map(|event| moved_in_directory(event))
+            .filter_map(|directory| self.filter_for(directory).map(|filter| (directory, filter)))
+            .map(|(directory, filter)| (directory, track_projects_below(service, directory, filter)))
+            .collect();
+        for (directory, count) in moved_in {
+            self.record_discovered(directory, count);
+        }
+
+        let (new_projects, changed_paths): (Vec<_>, Vec<_>) = events
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(_)
+                )
+            })
+            .flat_map(|event| event.paths.iter().cloned())
+            .partition(|path| self.is_new_project_manifest(path));
+
+        for project_path in new_projects.iter().filter_map(|manifest| manifest.parent()) {
+            info!("New Rust project detected: {}", project_path.display());
+            match track_new_project(service, project_path) {
+                Ok(()) => self.record_discovered(project_path, 1),
+                Err(e) => error!("Failed to add new project {}: {e}", project_path.display()),
+            }
+        }
+
+        if !changed_paths.is_empty() {
+            self.update_sizes(&changed_paths);
+        }
+
+        events.len()
+    }
+
+    /// Every project containing a changed path gets its totals adjusted, nested projects included.
+    fn update_sizes(&mut self, changed_paths: &[PathBuf]) {
+        let Ok(projects) = self.service.get_all_projects() else {
+            return;
+        };
+        let projects: HashMap<PathBuf, RustProject> = projects
+            .into_iter()
+            .map(|project| (project.path.clone(), project))
+            .collect();
+
+        let updated: HashMap<PathBuf, ProjectSizes> = changed_paths
+            .iter()
+            .flat_map(|path| {
+                path.ancestors()
+                    .filter(|ancestor| projects.contains_key(*ancestor))
+                    .map(move |project_path| (project_path.to_path_buf(), path))
+            })
+            .map(|(project_path, path)| {
+                let sizes = self.sizes.apply(&project_path, path);
+                (project_path, sizes)
+            })
+            .collect();
+
+        updated
+            .into_iter()
+            .filter_map(|(path, sizes)| projects.get(&path).map(|project| (project, sizes)))
+            .filter_map(|(project, sizes)| save_sizes(self.service, project, sizes, &self.sizes))
+            .for_each(|snapshot| self.pending_snapshots.push(snapshot));
+    }
+
+    /// Re-walks projects whose incremental totals are older than the reconcile interval.
+    fn reconcile_sizes(&mut self) {
+        self.sizes_checked_at = Instant::now();
+        let due = self.sizes.due_for_reconciliation();
+        if due.is_empty() {
+            return;
+        }
+
+        due.iter()
+            .map(|project_path| (project_path, self.sizes.reconcile(project_path)))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .for_each(|(project_path, sizes)| {
+                match self.service.find_project_by_path(project_path) {
+                    Ok(Some(project)) => self
+                        .pending_snapshots
+                        .extend(save_sizes(self.service, &project, sizes, &self.sizes)),
+                    Ok(None) => self.sizes.forget_below(project_path),
+                    Err(e) => warn!("Failed to reconcile {}: {e}", project_path.display()),
+                }
+            });
+    }
+}
+
+/// Which kind of watcher ended up watching a path
+enum Backend {
+    Native,
+    Poll,
+    FallbackPoll(notify::Error),
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Native => write!(f, "native events"),
+            Self::Poll => write!(f, "polling"),
+            Self::FallbackPoll(_) => write!(f, "polling after reaching the watch limit"),
+        }
+    }
+}
+
+fn forward_events(
+    sender: mpsc::Sender<DaemonMessage>,
+) -> impl FnMut(DebounceEventResult) + Send + 'static {
+    move |result| {
+        sender.send(DaemonMessage::Filesystem(result)).ok();
+    }
+}
+
+/// A directory renamed within the watched paths, reported by the debouncer as one `from -> to` event.
+fn renamed_directory(event: &DebouncedEvent) -> Option<(&Path, &Path)> {
+    match (event.kind, event.paths.as_slice()) {
+        (EventKind::Modify(ModifyKind::Name(RenameMode::Both)), [from, to]) if to.is_dir() => {
+            Some((from.as_path(), to.as_path()))
+        }
+        _ => None,
+    }
+}
+
+/// A directory that appeared by being moved, possibly bringing projects along.
+fn moved_in_directory(event: &DebouncedEvent) -> Option<&Path> {
+    match (event.kind, event.paths.as_slice()) {
+        (EventKind::Modify(ModifyKind::Name(RenameMode::Both)), [_, to])
+        | (EventKind::Modify(ModifyKind::Name(RenameMode::To)), [to])
+            if to.is_dir() =>
+        {
+            Some(to.as_path())
+        }
+        _ => None,
+    }
+}
+
+/// Deleted paths, including paths moved out of every watched directory.
+fn is_removal(event: &DebouncedEvent) -> bool {
+    matches!(
+        event.kind,
+        EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From))
+    )
+}
+
+fn relocate_projects<P: ProjectRepository + ?Sized, W: WatcherRepository + ?Sized>(
+    service: &ProjectService<P, W>,
+    from: &Path,
+    to: &Path,
+) {
+    match service.relocate_projects(from, to) {
+        Ok(0) => {}
+        Ok(moved) => info!(
+            "Relocated {moved} projects from {} to {}",
+            from.display(),
+            to.display()
+        ),
+        Err(e) => error!("Failed to relocate projects from {}: {e}", from.display()),
+    }
+}
+
+/// Untracks projects whose directory or `Cargo.toml` was deleted, or that lived below a deleted directory.
+fn untrack_removed_projects<P: ProjectRepository + ?Sized, W: WatcherRepository + ?Sized>(
+    service: &ProjectService<P, W>,
+    removed_paths: &[&PathBuf],
+) -> Vec<PathBuf> {
+    let Ok(projects) = service.get_all_projects() else {
+        return Vec::new();
+    };
+
+    projects
+        .into_iter()
+        .filter(|project| !project.path.join("Cargo.toml").exists())
+        .filter(|project| {
+            removed_paths.iter().any(|removed| {
+                project.path.starts_with(removed) || **removed == project.path.join("Cargo.toml")
+            })
+        })
+        .filter_map(|project| match service.remove_project(project.id) {
+            Ok(_) => {
+                info!(
+                    "Untracked removed project {} ({})",
+                    project.name,
+                    project.path.display()
+                );
+                Some(project.path)
+            }
+            Err(e) => {
+                error!("Failed to untrack {}: {e}", project.path.display());
+                None
+            }
+        })
+        .collect()
+}
+
+fn track_projects_below<P: ProjectRepository + ?Sized, W: WatcherRepository + ?Sized>(
+    service: &ProjectService<P, W>,
+    directory: &Path,
+    filter: &WatchFilter,
+) -> usize {
+    match collect_project_paths(directory, filter, &[])
+        .and_then(|paths| track_project_paths(service, paths))
+    {
+        Ok(result) => {
+            for project in &result.found_projects {
+                info!("Moved-in Rust project detected: {}", project.path.display());
+            }
+            result.added_count
+        }
+        Err(e) => {
+            error!("Failed to scan {}: {e}", directory.display());
+            0
+        }
+    }
+}
+
+/// A `Cargo.toml` that exists on disk and whose directory is not yet tracked.
+fn is_untracked_manifest<P: ProjectRepository + ?Sized, W: WatcherRepository + ?Sized>(
+    service: &ProjectService<P, W>,
+    path: &Path,
+) -> bool {
+    path.file_name() == Some(OsStr::new("Cargo.toml"))
+        && path.is_file()
+        && path
+            .parent()
+            .is_some_and(|project_path| !service.project_exists(project_path).unwrap_or(true))
+}
+
+fn track_new_project<P: ProjectRepository + ?Sized, W: WatcherRepository + ?Sized>(
+    service: &ProjectService<P, W>,
+    project_path: &Path,
+) -> ProjectsResult<()> {
+    let canonical_path = project_path
+        .canonicalize()
+        .unwrap_or_else(|_| project_path.to_path_buf());
+
+    // A crate added to a tracked workspace joins it as a member instead of becoming a project.
+    let workspace = service
+        .find_project_containing_path(&canonical_path)?
+        .filter(RustProject::is_workspace);
+    if workspace.as_ref().is_some_and(|workspace| workspace.has_member(&canonical_path)) {
+        return Ok(());
+    }
+
+    let project = create_rust_project_from_cargo_toml(&canonical_path)?;
+    let workspace = workspace.map(|workspace| RustProject {
+        members: read_workspace_members(&workspace.path),
+        ..workspace
+    });
+    service
+        .add_projects_with_members(workspace.into_iter().chain([project]).collect())
+        .map(drop)
+}
+
+/// Saves the new totals of a project along with the sizes of its workspace members, which the
+/// tracker already knows from walking the workspace. Returns the snapshot to record in the size
+/// history, `None` when nothing changed.
+fn save_sizes<P: ProjectRepository + ?Sized, W: WatcherRepository + ?Sized>(
+    service: &ProjectService<P, W>,
+    project: &RustProject,
+    sizes: ProjectSizes,
+    tracker: &SizeTracker,
+) -> Option<(ProjectId, SizeSnapshot)> {
+    let members: Vec<WorkspaceMember> = project
+        .members
+        .iter()
+        .map(|member| WorkspaceMember {
+            size_bytes: tracker
+                .size_below(&project.path, &member.path)
+                .unwrap_or(member.size_bytes),
+            ..member.clone()
+        })
+        .collect();
+    if project.size_bytes == sizes.total
+        && project.target_size_bytes == sizes.target
+        && members == project.members
+    {
+        return None;
+    }
+
+    let updated = RustProject {
+        size_bytes: sizes.total,
+        target_size_bytes: sizes.target,
+        last_modified: Utc::now(),
+        members,
+        ..project.clone()
+    };
+    let snapshot = SizeSnapshot::of(&updated);
+    service
+        .save_project(updated)
+        .map(|id| (id, snapshot))
+        .map_err(|e| warn!("Failed to update size of {}: {e}", project.name))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::RealFileSystem;
+    use crate::repositories::{FileHistoryRepository, FileProjectRepository, FileWatcherRepository};
+
+    type TestProjectService =
+        ProjectService<FileProjectRepository<RealFileSystem>, FileWatcherRepository<RealFileSystem>>;
+
+    /// Registries in a temporary directory, with a `work` directory next to them to watch.
+    struct Registries {
+        dir: tempfile::TempDir,
+        projects: TestProjectService,
+        watchers: WatcherService<FileWatcherRepository<RealFileSystem>>,
+    }
+
+    impl Registries {
+        fn new() -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            fs::create_dir(dir.path().join("work")).unwrap();
+            let file_system = Arc::new(RealFileSystem);
+            let watcher_repo = Arc::new(FileWatcherRepository::new(
+                file_system.clone(),
+                dir.path().join("watchers.ron"),
+            ));
+            let projects = ProjectService::new(
+                Arc::new(FileProjectRepository::new(
+                    file_system.clone(),
+                    dir.path().join("registry.ron"),
+                    dir.path().join("watchers.ron"),
+                )),
+                watcher_repo.clone(),
+                Arc::new(FileHistoryRepository::new(file_system, dir.path().join("history.ron"))),
+            );
+
+            Self {
+                dir,
+                projects,
+                watchers: WatcherService::new(watcher_repo),
+            }
+        }
+
+        fn work_dir(&self) -> PathBuf {
+            self.dir.path().join("work")
+        }
+
+        fn daemon(&self) -> Daemon<'_, FileProjectRepository<RealFileSystem>, FileWatcherRepository<RealFileSystem>> {
+            let (sender, _) = mpsc::channel();
+            Daemon::new(&self.projects, &self.watchers, sender, Duration::from_secs(60), Duration::from_secs(5))
+                .unwrap()
+        }
+
+        fn watcher(&self, mode: WatchMode) -> WatcherConfig {
+            WatcherConfig {
+                name: WatcherName::new("work".to_string()),
+                path: self.work_dir(),
+                created_at: Utc::now(),
+                system_managed: false,
+                rules: WatchRules::default(),
+                mode,
+                paused: false,
+                health: WatcherHealth::default(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_paused_watchers_are_not_watched() -> ProjectsResult<()> {
+        let registries = Registries::new();
+        let mut daemon = registries.daemon();
+        let watcher = registries.watcher(WatchMode::Native);
+        let save = |daemon: &mut Daemon<'_, _, _>, paused| {
+            daemon.handle_request(DaemonRequest::SaveWatcher(WatcherConfig {
+                paused,
+                ..watcher.clone()
+            }))
+        };
+
+        save(&mut daemon, false)?;
+        assert!(daemon.active_watchers.contains_key(&registries.work_dir()));
+
+        save(&mut daemon, true)?;
+        assert!(daemon.active_watchers.is_empty());
+        assert!(registries.watchers.get_watcher_by_name(&watcher.name)?.unwrap().paused);
+
+        save(&mut daemon, false)?;
+        assert!(daemon.active_watchers.contains_key(&registries.work_dir()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_poll_mode_watchers_are_polled() -> ProjectsResult<()> {
+        let registries = Registries::new();
+        let mut daemon = registries.daemon();
+        let watcher = registries.watcher(WatchMode::Poll { interval_secs: 30 });
+        daemon.handle_request(DaemonRequest::SaveWatcher(watcher))?;
+
+        let work_dir = registries.work_dir();
+        assert!(daemon.pollers.contains_key(&work_dir));
+        assert!(daemon.active_watchers[&work_dir].health.polling);
+        Ok(())
+    }
+
+    #[test]
+    fn test_watch_limit_falls_back_to_polling() -> ProjectsResult<()> {
+        let registries = Registries::new();
+        let mut daemon = registries.daemon();
+        let watcher = registries.watcher(WatchMode::Native);
+        daemon.handle_request(DaemonRequest::SaveWatcher(watcher.clone()))?;
+        let work_dir = registries.work_dir();
+        assert!(!daemon.pollers.contains_key(&work_dir));
+
+        let error = notify::Error::new(ErrorKind::MaxFilesWatch).add_path(work_dir.join("src"));
+        daemon.handle_debounce_result(Err(vec![error]));
+
+        assert!(daemon.pollers.contains_key(&work_dir));
+        let health = registries.watchers.get_watcher_by_name(&watcher.name)?.unwrap().health;
+        assert!(health.polling);
+        assert!(health.last_error.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_manifests_follow_the_configured_ignored_dirs() -> ProjectsResult<()> {
+        let registries = Registries::new();
+        let mut daemon = registries.daemon();
+        daemon.settings = Arc::new(ScanSettings {
+            ignored_dirs: vec!["vendor".to_string()],
+            ..ScanSettings::default()
+        });
+        daemon.handle_request(DaemonRequest::SaveWatcher(registries.watcher(WatchMode::Native)))?;
+
+        let manifest = |relative: &str| {
+            let path = registries.work_dir().join(relative).join("Cargo.toml");
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "").unwrap();
+            path
+        };
+        assert!(daemon.is_new_project_manifest(&manifest("target/package/game")));
+        assert!(!daemon.is_new_project_manifest(&manifest("vendor/serde")));
+        assert!(daemon.is_new_project_manifest(&manifest("game")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_size_history_is_recorded_on_flush() -> ProjectsResult<()> {
+        let registries = Registries::new();
+        let game = registries.work_dir().join("game");
+        fs::create_dir_all(game.join("src"))?;
+        fs::write(game.join("Cargo.toml"), "[package]\nname = \"game\"\n")?;
+        registries.projects.add_project(RustProject {
+            id: ProjectId::new(0),
+            name: ProjectName::new("game".to_string()),
+            path: game.clone(),
+            version: ProjectVersion::new("0.1.0".to_string()),
+            created_at: Utc::now(),
+            last_modified: Utc::now(),
+            size_bytes: FileSize::new(0),
+            target_size_bytes: FileSize::new(0),
+            dependencies_count: DependencyCount::new(0),
+            estimated_build_time_seconds: TimingDuration::new(0),
+            project_type: ProjectType::Package,
+            annotations: ProjectAnnotations::default(),
+            members: Vec::new(),
+        })?;
+        let id = registries.projects.find_project_by_path(&game)?.unwrap().id;
+        let recorded_size = || -> ProjectsResult<FileSize> {
+            Ok(registries.projects.get_size_history(id)?.last().unwrap().size_bytes)
+        };
+
+        let mut daemon = registries.daemon();
+        fs::write(game.join("src/main.rs"), "fn main() {}")?;
+        daemon.update_sizes(&[game.join("src/main.rs")]);
+
+        let saved = registries.projects.find_project_by_path(&game)?.unwrap().size_bytes;
+        assert_ne!(saved, FileSize::new(0));
+        assert_eq!(recorded_size()?, FileSize::new(0));
+
+        daemon.flush_snapshots();
+        assert_eq!(recorded_size()?, saved);
+        assert_eq!(daemon.pending_snapshots, Vec::new());
+        Ok(())
+    }
+}
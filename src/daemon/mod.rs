@@ -0,0 +1,7 @@
+pub mod control;
+pub mod event_loop;
+pub mod size_tracker;
+pub mod systemd;
+
+pub use control::{DaemonClient, DaemonRequest, DaemonResponse, DaemonStatus};
+pub use event_loop::{DaemonSummary, run_daemon};
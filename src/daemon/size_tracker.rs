@@ -0,0 +1,201 @@
+use crate::commands::collect_file_sizes;
+use crate::types::FileSize;
+use std::{
+    collections::{BTreeMap, HashMap, hash_map::Entry},
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProjectSizes {
+    pub total: FileSize,
+    pub target: FileSize,
+}
+
+/// Keeps per-project size totals current from the paths in watcher events.
+///
+/// A `cargo build` then costs a `stat` per changed file instead of two full walks of the project
+/// per debounced batch. Each project is walked once when first touched and again every
+/// `reconcile_interval` to correct drift from missed events.
+pub struct SizeTracker {
+    projects: HashMap<PathBuf, TrackedProject>,
+    reconcile_interval: Duration,
+}
+
+struct TrackedProject {
+    target_path: PathBuf,
+    /// Ordered so that everything below a directory is one contiguous range
+    files: BTreeMap<PathBuf, u64>,
+    total: u64,
+    target: u64,
+    reconciled_at: Instant,
+}
+
+impl SizeTracker {
+    pub fn new(reconcile_interval: Duration) -> Self {
+        Self {
+            projects: HashMap::new(),
+            reconcile_interval,
+        }
+    }
+
+    /// Accounts for `changed_path` having been created, modified or removed inside the project.
+    pub fn apply(&mut self, project_path: &Path, changed_path: &Path) -> ProjectSizes {
+        match self.projects.entry(project_path.to_path_buf()) {
+            Entry::Vacant(vacant) => vacant.insert(TrackedProject::walk(project_path)).sizes(),
+            Entry::Occupied(occupied) => {
+                let project = occupied.into_mut();
+                project.apply(changed_path);
+                project.sizes()
+            }
+        }
+    }
+
+    pub fn reconcile(&mut self, project_path: &Path) -> ProjectSizes {
+        let project = TrackedProject::walk(project_path);
+        let sizes = project.sizes();
+        self.projects.insert(project_path.to_path_buf(), project);
+        sizes
+    }
+
+    pub fn due_for_reconciliation(&self) -> Vec<PathBuf> {
+        self.projects
+            .iter()
+            .filter(|(_, project)| project.reconciled_at.elapsed() >= self.reconcile_interval)
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// Size of the files at or below `path` inside a tracked project, e.g. of a workspace member.
+    pub fn size_below(&self, project_path: &Path, path: &Path) -> Option<FileSize> {
+        self.projects
+            .get(project_path)
+            .map(|project| FileSize::new(project.size_below(path)))
+    }
+
+    /// Drops the totals of projects at or below `path`, e.g. after they were moved or deleted.
+    pub fn forget_below(&mut self, path: &Path) {
+        self.projects
+            .retain(|project_path, _| !project_path.starts_with(path));
+    }
+}
+
+impl TrackedProject {
+    fn walk(project_path: &Path) -> Self {
+        let mut project = Self {
+            target_path: project_path.join("target"),
+            files: BTreeMap::new(),
+            total: 0,
+            target: 0,
+            reconciled_at: Instant::now(),
+        };
+        for (path, size) in collect_file_sizes(project_path) {
+            project.set(path, Some(size));
+        }
+        project
+    }
+
+    const fn sizes(&self) -> ProjectSizes {
+        ProjectSizes {
+            total: FileSize::new(self.total),
+            target: FileSize::new(self.target),
+        }
+    }
+
+    fn apply(&mut self, changed_path: &Path) {
+        match fs::symlink_metadata(changed_path) {
+            Ok(metadata) if metadata.is_file() => {
+                self.set(changed_path.to_path_buf(), Some(metadata.len()));
+            }
+            Ok(metadata) if metadata.is_dir() => {
+                self.remove_below(changed_path);
+                for (path, size) in collect_file_sizes(changed_path) {
+                    self.set(path, Some(size));
+                }
+            }
+            // Gone, or a symlink the size walk would not count either
+            _ => self.remove_below(changed_path),
+        }
+    }
+
+    fn size_below(&self, path: &Path) -> u64 {
+        self.files
+            .range(path.to_path_buf()..)
+            .take_while(|(file, _)| file.starts_with(path))
+            .map(|(_, size)| size)
+            .sum()
+    }
+
+    fn remove_below(&mut self, path: &Path) {
+        let removed: Vec<PathBuf> = self
+            .files
+            .range(path.to_path_buf()..)
+            .take_while(|(file, _)| file.starts_with(path))
+            .map(|(file, _)| file.clone())
+            .collect();
+        for file in removed {
+            self.set(file, None);
+        }
+    }
+
+    fn set(&mut self, path: PathBuf, size: Option<u64>) {
+        let in_target = path.starts_with(&self.target_path);
+        let previous = match size {
+            Some(size) => self.files.insert(path, size),
+            None => self.files.remove(&path),
+        }
+        .unwrap_or(0);
+        let current = size.unwrap_or(0);
+
+        self.total = (self.total + current).saturating_sub(previous);
+        if in_target {
+            self.target = (self.target + current).saturating_sub(previous);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_tracks_created_modified_and_removed_files() {
+        let project = tempfile::tempdir().unwrap();
+        let target = project.path().join("target").join("debug");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(project.path().join("Cargo.toml"), "x".repeat(10)).unwrap();
+
+        let mut tracker = SizeTracker::new(Duration::from_secs(3600));
+        let initial = tracker.apply(project.path(), project.path());
+        assert_eq!(initial.total.bytes(), 10);
+        assert_eq!(initial.target.bytes(), 0);
+
+        fs::write(target.join("app"), "x".repeat(100)).unwrap();
+        let built = tracker.apply(project.path(), &target.join("app"));
+        assert_eq!(built.total.bytes(), 110);
+        assert_eq!(built.target.bytes(), 100);
+
+        fs::write(target.join("app"), "x".repeat(40)).unwrap();
+        assert_eq!(
+            tracker
+                .apply(project.path(), &target.join("app"))
+                .total
+                .bytes(),
+            50
+        );
+
+        fs::remove_dir_all(project.path().join("target")).unwrap();
+        let cleaned = tracker.apply(project.path(), &project.path().join("target"));
+        assert_eq!(cleaned.total.bytes(), 10);
+        assert_eq!(cleaned.target.bytes(), 0);
+        assert_eq!(cleaned, tracker.reconcile(project.path()));
+
+        let member = project.path().join("crates").join("render");
+        fs::create_dir_all(&member).unwrap();
+        fs::write(member.join("lib.rs"), "x".repeat(7)).unwrap();
+        tracker.apply(project.path(), &member.join("lib.rs"));
+        assert_eq!(tracker.size_below(project.path(), &member).map(FileSize::bytes), Some(7));
+        assert_eq!(tracker.size_below(&member, &member), None);
+    }
+}
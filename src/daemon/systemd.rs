@@ -0,0 +1,137 @@
+use crate::services::{HOME_ENV, active_profile, default_config_dir};
+use crate::types::*;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// `cargo-projects.service`, or `cargo-projects-<profile>.service` so every profile can run its
+/// own daemon.
+pub fn unit_name() -> ProjectsResult<String> {
+    Ok(active_profile()?.map_or_else(
+        || "cargo-projects.service".to_string(),
+        |profile| format!("cargo-projects-{profile}.service"),
+    ))
+}
+
+/// `~/.config/systemd/user/<unit name>`
+pub fn unit_path() -> ProjectsResult<PathBuf> {
+    let unit_name = unit_name()?;
+    dirs::config_dir()
+        .ok_or(ProjectsError::ConfigDirectoryNotFound)
+        .map(|dir| dir.join("systemd").join("user").join(unit_name))
+}
+
+/// Unit for the currently running binary, pinned to the config directory this invocation uses.
+pub fn current_unit() -> ProjectsResult<String> {
+    let binary = env::current_exe()?;
+    let config_dir = default_config_dir()?;
+    Ok(render_unit(&binary, &config_dir))
+}
+
+/// The service gets `CARGO_PROJECTS_HOME` set to the config directory, profile included, so it
+/// uses the same `config.ron` regardless of the login environment.
+pub fn render_unit(binary: &Path, config_dir: &Path) -> String {
+    let binary = quote(&binary.to_string_lossy());
+
+    format!(
+        "[Unit]
+Description=cargo-projects watcher daemon
+Documentation=https://codeberg.org/DanielBellman/cargo-projects
+
+[Service]
+Type=simple
+ExecStart={binary} projects daemon run
+ExecStop={binary} projects daemon stop
+Environment={}
+Environment=RUST_LOG=info
+Restart=on-failure
+RestartSec=10
+
+[Install]
+WantedBy=default.target
+",
+        quote(&format!("{HOME_ENV}={}", config_dir.display()))
+    )
+}
+
+pub fn install_unit(unit: &str) -> ProjectsResult<PathBuf> {
+    let path = unit_path()?;
+    let unit_name = unit_name()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, unit)?;
+
+    systemctl(&["daemon-reload"])?;
+    systemctl(&["enable", "--now", &unit_name])?;
+    Ok(path)
+}
+
+/// Returns `None` when no unit was installed.
+pub fn uninstall_unit() -> ProjectsResult<Option<PathBuf>> {
+    let path = unit_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    systemctl(&["disable", "--now", &unit_name()?])?;
+    fs::remove_file(&path)?;
+    systemctl(&["daemon-reload"])?;
+    Ok(Some(path))
+}
+
+fn systemctl(args: &[&str]) -> ProjectsResult<()> {
+    let output = Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .output()?;
+    output
+        .status
+        .success()
+        .then_some(())
+        .ok_or_else(|| ProjectsError::SystemctlFailed {
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        })
+}
+
+/// Quotes a value for a unit file when it contains whitespace, quotes or backslashes.
+fn quote(value: &str) -> String {
+    if value.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\') {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_unit_points_at_binary_and_config() {
+        let unit = render_unit(
+            Path::new("/home/alice/.cargo/bin/cargo-projects"),
+            Path::new("/home/alice/.config/cargo-projects"),
+        );
+
+        assert!(
+            unit.contains("ExecStart=/home/alice/.cargo/bin/cargo-projects projects daemon run\n")
+        );
+        assert!(unit.contains("Environment=CARGO_PROJECTS_HOME=/home/alice/.config/cargo-projects\n"));
+    }
+
+    #[test]
+    fn test_render_unit_quotes_paths_with_spaces() {
+        let unit = render_unit(
+            Path::new("/opt/my tools/cargo-projects"),
+            Path::new("/home/alice/my config/cargo-projects"),
+        );
+
+        assert!(unit.contains("ExecStart=\"/opt/my tools/cargo-projects\" projects daemon run\n"));
+        assert!(unit.contains(
+            "Environment=\"CARGO_PROJECTS_HOME=/home/alice/my config/cargo-projects\"\n"
+        ));
+    }
+}
@@ -2,20 +2,34 @@ use crate::types::{ProjectsError, ProjectsResult};
 use std::{
     collections::HashMap,
     env,
-    fs::{self, write, Metadata},
-    io::{Error as IoError, ErrorKind},
+    ffi::OsString,
+    fs::{self, File, Metadata, OpenOptions},
+    io::{Error as IoError, ErrorKind, Write},
     path::{Path, PathBuf},
 };
 
 pub trait FileSystem: Send + Sync {
     fn read_to_string(&self, path: &Path) -> ProjectsResult<String>;
+    /// Replaces the file atomically, so readers and crashes never see a partially written file.
     fn write(&self, path: &Path, content: &str) -> ProjectsResult<()>;
+    /// Blocks until this process holds the exclusive advisory lock for `path`, released when the
+    /// returned guard is dropped.
+    fn lock(&self, path: &Path) -> ProjectsResult<FileLock>;
     fn exists(&self, path: &Path) -> bool;
     fn create_dir_all(&self, path: &Path) -> ProjectsResult<()>;
+    /// Paths of the entries in a directory, empty when it does not exist.
+    fn read_dir(&self, path: &Path) -> ProjectsResult<Vec<PathBuf>>;
+    fn remove_file(&self, path: &Path) -> ProjectsResult<()>;
     #[allow(dead_code)]
     fn metadata(&self, path: &Path) -> ProjectsResult<Metadata>;
 }
 
+/// Guard for the lock taken by [`FileSystem::lock`]
+#[must_use = "the lock is released as soon as the guard is dropped"]
+pub struct FileLock {
+    _file: Option<File>,
+}
+
 pub struct RealFileSystem;
 
 impl FileSystem for RealFileSystem {
@@ -23,11 +37,37 @@ impl FileSystem for RealFileSystem {
         Ok(fs::read_to_string(path)?)
     }
 
-  fn write(&self, path: &Path, content: &str) -> ProjectsResult<()> {
-      path.parent()
-          .map_or(Ok(()), |parent| self.create_dir_all(parent))
-          .and_then(|_| Ok(write(path, content)?))
-  }
+    fn write(&self, path: &Path, content: &str) -> ProjectsResult<()> {
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent)?;
+        }
+
+        let temp_path = sibling_path(path, &format!("tmp-{}", std::process::id()));
+        let written = File::create(&temp_path)
+            .and_then(|mut file| {
+                file.write_all(content.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|()| fs::rename(&temp_path, path));
+        if written.is_err() {
+            fs::remove_file(&temp_path).ok();
+        }
+        Ok(written?)
+    }
+
+    fn lock(&self, path: &Path) -> ProjectsResult<FileLock> {
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(sibling_path(path, "lock"))?;
+        file.lock()?;
+        Ok(FileLock { _file: Some(file) })
+    }
 
     fn exists(&self, path: &Path) -> bool {
         path.exists()
@@ -37,11 +77,33 @@ impl FileSystem for RealFileSystem {
         Ok(fs::create_dir_all(path)?)
     }
 
+    fn read_dir(&self, path: &Path) -> ProjectsResult<Vec<PathBuf>> {
+        match fs::read_dir(path) {
+            Ok(entries) => entries
+                .map(|entry| entry.map(|entry| entry.path()).map_err(Into::into))
+                .collect(),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn remove_file(&self, path: &Path) -> ProjectsResult<()> {
+        Ok(fs::remove_file(path)?)
+    }
+
     fn metadata(&self, path: &Path) -> ProjectsResult<Metadata> {
         Ok(fs::metadata(path)?)
     }
 }
 
+/// `registry.ron` -> `registry.ron.<suffix>`, in the same directory so a rename stays atomic.
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().map_or_else(OsString::new, ToOwned::to_owned);
+    file_name.push(".");
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
 #[cfg(test)]
 pub struct MockFileSystem {
     pub files: HashMap<PathBuf, String>,
@@ -102,6 +164,10 @@ impl FileSystem for MockFileSystem {
         Ok(())
     }
 
+    fn lock(&self, _path: &Path) -> ProjectsResult<FileLock> {
+        Ok(FileLock { _file: None })
+    }
+
     fn exists(&self, path: &Path) -> bool {
         self.files.contains_key(path)
     }
@@ -110,6 +176,19 @@ impl FileSystem for MockFileSystem {
         Ok(())
     }
 
+    fn read_dir(&self, path: &Path) -> ProjectsResult<Vec<PathBuf>> {
+        Ok(self
+            .files
+            .keys()
+            .filter(|file| file.parent() == Some(path))
+            .cloned()
+            .collect())
+    }
+
+    fn remove_file(&self, _path: &Path) -> ProjectsResult<()> {
+        Ok(())
+    }
+
     fn metadata(&self, path: &Path) -> ProjectsResult<Metadata> {
         let mock_metadata = self.metadata_map.get(path)
             .ok_or_else(|| ProjectsError::Io(IoError::new(
@@ -17,6 +17,7 @@
 
 pub mod types;
 pub mod commands;
+pub mod daemon;
 pub mod output;
 pub mod infrastructure;
 pub mod services;
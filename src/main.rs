@@ -1,30 +1,21 @@
-use chrono::Utc;
 use clap::Parser;
-use log::{error, info};
-use notify::{EventKind, RecursiveMode};
-use notify_debouncer_full::new_debouncer;
-use services::{create_default_project_service, service_factory};
 use std::{
-    ffi::OsStr,
     io,
-    path::{Path, PathBuf},
     process::{self},
-    sync::mpsc,
-    time::Duration,
 };
 
 mod commands;
+mod daemon;
 mod infrastructure;
 mod output;
 mod repositories;
 mod services;
 mod types;
 
-use commands::cli::{CargoCli, ProjectsCommand};
+use commands::cli::{BackupCommand, CargoCli, DaemonCommand, ProjectsCommand, WatchersCommand};
 use commands::*;
 use output::output::*;
-use types::*;
-use crate::commands::scan::determine_project_type;
+use types::ImportMode;
 
 fn default_error_handler(error: &anyhow::Error, stderr: &mut dyn io::Write) {
     writeln!(stderr, "Error: {}", error).ok();
@@ -51,6 +42,10 @@ fn main() {
 
 fn run() -> anyhow::Result<bool> {
     let CargoCli::Projects(args) = CargoCli::parse();
+    services::set_config_location(services::ConfigLocation {
+        dir: args.config_dir,
+        profile: args.profile,
+    });
     let mut stdout = io::stdout();
     let mut stderr = io::stderr();
 
@@ -72,13 +67,12 @@ fn run() -> anyhow::Result<bool> {
 //#TODO can it get cleaner?
 fn execute_command(command: ProjectsCommand) -> CommandResult<String> {
     match command {
-        ProjectsCommand::List { watcher_name } => {
-            let project_list_of_watcher = handle_list_projects(watcher_name.as_ref())?;
-            Ok(format_project_list(&project_list_of_watcher))
+        ProjectsCommand::List { watcher_name, tags, members } => {
+            let project_list_of_watcher = handle_list_projects(watcher_name.as_ref(), &tags)?;
+            Ok(format_project_list(&project_list_of_watcher, members))
         }
-        ProjectsCommand::Watchers => {
-            let list_of_watchers = handle_list_watchers()?;
-            Ok(format_watcher_list(&list_of_watchers))
+        ProjectsCommand::Watchers { action } => {
+            execute_watchers_command(action.unwrap_or(WatchersCommand::List))
         }
         ProjectsCommand::CleanWatchers => {
             handle_clean_watchers()?;
@@ -90,39 +84,60 @@ fn execute_command(command: ProjectsCommand) -> CommandResult<String> {
             let watched_directory = handle_scan_directory(&path_to_directory_watched)?;
             Ok(format_scan_result(&watched_directory))
         }
-        ProjectsCommand::Clean { project_id } => {
-            let project_name = handle_clean_project(project_id)?;
-            Ok(format_clean_result(&project_name))
+        ProjectsCommand::Clean {
+            project,
+            tags,
+            force,
+        } => {
+            if let Some(project) = project {
+                let project_name = handle_clean_project(&project, force)?;
+                return Ok(format_clean_result(&project_name));
+            }
+
+            let result = handle_clean_tagged_projects(&tags, force)?;
+            Ok(format_clean_tagged_result(&result))
         }
         ProjectsCommand::Watch {
             project_path,
             name,
             system_wide,
+            rules,
+            mode,
         } => {
             if system_wide {
-                return Err(
-                    "System-wide monitoring not yet implemented. Use --project-path for now."
-                        .into(),
-                );
+                let result = handle_watch_system_wide()?;
+                return Ok(format_system_wide_result(&result));
             }
 
-            let watcher_name = name.unwrap_or_else(|| {
-                WatcherName::new(
-                    project_path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("unnamed")
-                        .to_string(),
-                )
-            });
-
-            let watcher_config = handle_watch(&project_path, &watcher_name)?;
-            Ok(format!(
-                "Started watching {} at {}",
-                watcher_config.name,
-                watcher_config.path.display()
-            ))
+            let watcher = handle_add_watcher(&project_path, name, rules.into(), mode.into())?;
+            Ok(format_watcher_added(&watcher))
         }
+        ProjectsCommand::Daemon { action } => match action.unwrap_or(DaemonCommand::Run) {
+            DaemonCommand::Run => {
+                let summary = handle_daemon_run()?;
+                Ok(format_daemon_result(&summary))
+            }
+            DaemonCommand::Status => {
+                let status = handle_daemon_status()?;
+                Ok(format_daemon_status(&status))
+            }
+            DaemonCommand::Reload => {
+                handle_daemon_reload()?;
+                Ok(format_daemon_reload_result())
+            }
+            DaemonCommand::Stop => {
+                handle_daemon_stop()?;
+                Ok(format_daemon_stop_result())
+            }
+            DaemonCommand::Install { print } => {
+                let result = handle_daemon_install(print)?;
+                Ok(format_daemon_install_result(&result))
+            }
+            DaemonCommand::Uninstall => {
+                let removed = handle_daemon_uninstall()?;
+                Ok(format_daemon_uninstall_result(removed.as_deref()))
+            }
+        },
         ProjectsCommand::Update => {
             let result = handle_update_projects()?;
             Ok(format_update_result(&result))
@@ -131,113 +146,111 @@ fn execute_command(command: ProjectsCommand) -> CommandResult<String> {
             handle_refresh_timing();
             Ok(format_refresh_result())
         }
+        ProjectsCommand::History { project } => {
+            let result = handle_project_history(&project)?;
+            Ok(format_history_result(&result))
+        }
+        ProjectsCommand::Tag { project, edits } => {
+            let project = handle_tag_project(&project, &edits)?;
+            Ok(format_tag_result(&project))
+        }
+        ProjectsCommand::Pin { project } => {
+            let project = handle_pin_project(&project, true)?;
+            Ok(format_pin_result(&project))
+        }
+        ProjectsCommand::Unpin { project } => {
+            let project = handle_pin_project(&project, false)?;
+            Ok(format_pin_result(&project))
+        }
+        ProjectsCommand::Note {
+            project,
+            text,
+            clear,
+        } => {
+            let project = if text.is_some() || clear {
+                handle_set_note(&project, text)?
+            } else {
+                handle_show_note(&project)?
+            };
+            Ok(format_note_result(&project))
+        }
+        ProjectsCommand::Prune { dry_run } => {
+            let result = handle_prune_projects(dry_run)?;
+            Ok(format_prune_result(&result))
+        }
+        ProjectsCommand::Remove { project } => {
+            let removed = handle_remove_project(&project)?;
+            Ok(format_remove_result(&removed))
+        }
+        ProjectsCommand::Renumber => {
+            let result = handle_renumber_projects()?;
+            Ok(format_renumber_result(&result))
+        }
+        ProjectsCommand::Export { format, output } => {
+            let result = handle_export_registry(format, output.as_deref())?;
+            Ok(format_export_result(result))
+        }
+        ProjectsCommand::Import {
+            file,
+            format,
+            rewrites,
+            replace,
+        } => {
+            let mode = if replace { ImportMode::Replace } else { ImportMode::Merge };
+            let result = handle_import_registry(&file, format, &rewrites, mode)?;
+            Ok(format_import_result(&result))
+        }
+        ProjectsCommand::Backup { action } => match action.unwrap_or(BackupCommand::List) {
+            BackupCommand::List => {
+                let backups = handle_list_backups()?;
+                Ok(format_backup_list(&backups))
+            }
+            BackupCommand::Restore { timestamp } => {
+                let restored = handle_restore_backup(timestamp)?;
+                Ok(format_restore_result(timestamp, &restored))
+            }
+        },
     }
 }
 
-
-fn handle_watch(project_path: &std::path::Path, watcher_name: &WatcherName) -> CommandResult<WatcherConfig> {
-    use services::service_factory::create_default_watcher_service;
-    
-    let canonical_path = project_path
-        .canonicalize()
-        .unwrap_or_else(|_| project_path.to_path_buf());
-        
-    let watcher_config = create_new_watcher_config(watcher_name, &canonical_path);
-    
-    create_default_watcher_service()
-        .and_then(|service| service.add_watcher(watcher_config.clone()))
-        .map(|_| watcher_config)
-}
-
-fn create_project_from_path(path: &PathBuf) -> CommandResult<RustProject> {
-
-    let cargo_toml_path = path.join("Cargo.toml");
-    let project_type = determine_project_type(&cargo_toml_path)?;
-
-    match project_type {
-        ProjectType::Package | ProjectType::WorkspaceWithPackage => 
-            create_package_project(path, project_type),
-        ProjectType::PureWorkspace => 
-            create_workspace_project(path),
-        ProjectType::Malformed => 
-            Ok(create_malformed_project(path)),
-    }
-}
-
- fn handle_new_project_detected(project_path: &Path) -> CommandResult {
-    create_default_project_service().and_then(|service| {
-        service
-            .find_project_by_path(project_path)?.ok_or("Project not found".into())
-            .and_then(|project| service.add_project(project))
-    })
- }
-
-fn register_watcher(name: &WatcherName, path: &Path) -> CommandResult {
-
-    service_factory::create_default_watcher_service().and_then(|service| {
-        service.add_watcher(create_new_watcher_config(name, path))
-    })
-}
-
-fn update_project_size_if_exists(changed_path: &Path) {
-    service_factory::create_default_project_service().ok().and_then(|service| {
-        service.find_project_containing_path(changed_path).ok()?
-            .map(|project| service.add_project(update_project_size_values(project)).ok())
-    });
-}
-
-//#TODO fix that
-fn watch<P: AsRef<Path>>(path: P) -> notify::Result<()> {    
-    let (tx, rx) = mpsc::channel();
-    let mut debouncer = new_debouncer(Duration::from_secs(2), None, tx)?;
-    
-    debouncer.watch(path.as_ref(), RecursiveMode::Recursive)?;
-    info!("Watching for new Rust projects in: {}", path.as_ref().display());
-
-    rx.into_iter()
-        .for_each(|result| match result {
-            Ok(events) => events
-                .into_iter()
-                .filter_map(|event| event.paths.first().map(|path| (path.clone(), event.kind)))
-                .for_each(|(path, kind)| {
-                    match (path.file_name(), kind) {
-                        (Some(name), EventKind::Create(_)) if name == OsStr::new("Cargo.toml") => {
-                            path.parent()
-                                .map(|project_path| {
-                                    info!("New Rust project detected: {}", project_path.display());
-                                    handle_new_project_detected(project_path)
-                                        .map_err(|e| error!("Failed to add new project {}: {}", project_path.display(), e))
-                                })
-                                .transpose()
-                                .ok();
-                        }
-                        (_, EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(_)) => {
-                            update_project_size_if_exists(&path);
-                        }
-                        _ => {}
-                    }
-                }),
-            Err(errors) => errors
-                .into_iter()
-                .for_each(|error| error!("Watch error: {error:?}")),
-        });
-
-    Ok(())
-}
-
-fn update_project_size_values(project: RustProject) -> RustProject {
-    RustProject {
-        size_bytes: FileSize::new(calculate_project_size(&project.path)),
-        target_size_bytes: FileSize::new(calculate_target_size(&project.path)),
-        last_modified: Utc::now(),
-        ..project
-    }
-}
-
-fn create_new_watcher_config(name: &WatcherName, path: &Path) -> WatcherConfig {
-    WatcherConfig {
-        name: name.clone(),
-        path: path.to_path_buf(),
-        created_at: Utc::now(),
+fn execute_watchers_command(command: WatchersCommand) -> CommandResult<String> {
+    match command {
+        WatchersCommand::List => {
+            let list_of_watchers = handle_list_watchers()?;
+            Ok(format_watcher_list(&list_of_watchers))
+        }
+        WatchersCommand::Add {
+            name,
+            path,
+            rules,
+            mode,
+        } => {
+            let watcher = handle_add_watcher(&path, name, rules.into(), mode.into())?;
+            Ok(format_watcher_added(&watcher))
+        }
+        WatchersCommand::Remove { name } => {
+            handle_remove_watcher(&name)?;
+            Ok(format_watcher_removed(&name))
+        }
+        WatchersCommand::Rename { name, new_name } => {
+            let watcher = handle_rename_watcher(&name, &new_name)?;
+            Ok(format_watcher_renamed(&name, &watcher))
+        }
+        WatchersCommand::Edit { name, edit } => {
+            let watcher = handle_edit_watcher(&name, edit.into())?;
+            Ok(format_watcher_edited(&watcher))
+        }
+        WatchersCommand::Show { name } => {
+            let details = handle_show_watcher(&name)?;
+            Ok(format_watcher_details(&details))
+        }
+        WatchersCommand::Pause { name } => {
+            let watcher = handle_pause_watcher(&name)?;
+            Ok(format_watcher_pause_result(&watcher))
+        }
+        WatchersCommand::Resume { name } => {
+            let watcher = handle_resume_watcher(&name)?;
+            Ok(format_watcher_pause_result(&watcher))
+        }
     }
 }
@@ -1,6 +1,19 @@
 use crate::commands::{
-    list::ProjectListResult, scan::ScanResult, update::UpdateResult, watchers::WatcherListResult,
+    clean::CleanResult,
+    daemon::InstallResult,
+    export::ExportResult,
+    history::HistoryResult,
+    import::ImportResult,
+    list::ProjectListResult, prune::PruneResult, renumber::RenumberResult, scan::ScanResult, system_wide::SystemWideResult, update::UpdateResult,
+    watchers::{WatcherDetails, WatcherListResult},
 };
+use crate::types::{
+    BackupTimestamp, FileSize, ImportMode, PruneReason, RustProject, Tag, WatchMode, WatcherConfig, WatcherName,
+};
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use crate::daemon::{DaemonStatus, DaemonSummary};
+use crate::repositories::Backup;
 use std::io::{self, Write};
 use tabled::{
     Table, Tabled,
@@ -10,7 +23,7 @@ use tabled::{
 #[derive(Tabled)]
 struct ProjectTableRow {
     #[tabled(rename = "ID")]
-    id: u32,
+    id: String,
     #[tabled(rename = "Name")]
     name: String,
     #[tabled(rename = "Size (GB)")]
@@ -19,64 +32,215 @@ struct ProjectTableRow {
     cache: String,
     #[tabled(rename = "Check Time")]
     check_time: String,
+    #[tabled(rename = "Tags")]
+    tags: String,
 }
 
-pub fn format_project_list(result: &ProjectListResult) -> String {
+/// One row per project, followed by a row per member of a workspace when `expand_members` is set.
+pub fn format_project_list(result: &ProjectListResult, expand_members: bool) -> String {
     match result.projects.is_empty() {
         true => "No projects found.".to_string(),
         false => format!(
-            "{}\nTotal: {} projects",
+            "{}\nTotal: {} projects{}",
             Table::new(
                 &result
                     .projects
                     .iter()
-                    .map(|p| ProjectTableRow {
-                        id: p.id.get(),
-                        name: p.name.to_string(),
-                        size: format!("{:.3}", p.size_bytes.as_gb()),
-                        cache: format!("{:.3}", p.target_size_bytes.as_gb()),
-                        check_time: match p.estimated_build_time_seconds.seconds() {
-                            0 => "Unknown".to_string(),
-                            s => format_build_time(s),
-                        },
+                    .flat_map(|p| {
+                        let members = p.members.iter().filter(|_| expand_members).map(|member| {
+                            ProjectTableRow {
+                                id: String::new(),
+                                name: format!("  └ {}", member.name),
+                                size: format!("{:.3}", member.size_bytes.as_gb()),
+                                cache: "-".to_string(),
+                                check_time: String::new(),
+                                tags: String::new(),
+                            }
+                        });
+                        std::iter::once(ProjectTableRow {
+                            id: p.id.to_string(),
+                            name: format_project_name(p, result.missing.contains(&p.id)),
+                            size: format!("{:.3}", p.size_bytes.as_gb()),
+                            cache: format!("{:.3}", p.target_size_bytes.as_gb()),
+                            check_time: match p.estimated_build_time_seconds.seconds() {
+                                0 => "Unknown".to_string(),
+                                s => format_build_time(s),
+                            },
+                            tags: format_tags(p),
+                        })
+                        .chain(members)
                     })
                     .collect::<Vec<_>>()
             )
             .with(Style::modern())
             .with(Modify::new(Columns::new(0..1)).with(Alignment::right()))
             .with(Modify::new(Columns::new(2..5)).with(Alignment::right())),
-            result.total_count
+            result.total_count,
+            match result.missing.len() {
+                0 => String::new(),
+                count => format!(", {count} missing on disk"),
+            }
         ),
     }
 }
 
+/// The project name followed by what sets the project apart, like `game (3 members, pinned)`.
+fn format_project_name(project: &RustProject, missing: bool) -> String {
+    let members = match project.members.len() {
+        0 => None,
+        1 => Some("1 member".to_string()),
+        count => Some(format!("{count} members")),
+    };
+    let remarks: Vec<String> = members
+        .into_iter()
+        .chain(project.annotations.pinned.then(|| "pinned".to_string()))
+        .chain(missing.then(|| "missing".to_string()))
+        .collect();
+
+    if remarks.is_empty() {
+        project.name.to_string()
+    } else {
+        format!("{} ({})", project.name, remarks.join(", "))
+    }
+}
+
+#[derive(Tabled)]
+struct WatcherTableRow {
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "State")]
+    state: &'static str,
+    #[tabled(rename = "Last Event")]
+    last_event: String,
+    #[tabled(rename = "Found")]
+    found: usize,
+    #[tabled(rename = "Last Error")]
+    last_error: String,
+}
+
 pub fn format_watcher_list(result: &WatcherListResult) -> String {
     match result.watchers.is_empty() {
         true => "No watchers configured.".to_string(),
         false => format!(
-            "{:<20} {:<50} {:<20}\n{:<20} {:<50} 
-  {:<20}\n{}",
-            "Name",
-            "Path",
-            "Created",
-            "----",
-            "----",
-            "-------",
-            result
-                .watchers
-                .iter()
-                .map(|w| format!(
-                    "{:<20} {:<50} {:<20}",
-                    w.name.as_str(),
-                    w.path.display(),
-                    w.created_at.format("%Y-%m-%d %H:%M")
-                ))
-                .collect::<Vec<_>>()
-                .join("\n")
+            "{}{}",
+            Table::new(
+                result
+                    .watchers
+                    .iter()
+                    .map(|w| WatcherTableRow {
+                        name: w.name.to_string(),
+                        path: w.path.display().to_string(),
+                        state: watcher_state(w, result.active.as_ref()),
+                        last_event: format_optional_time(w.health.last_event_at),
+                        found: w.health.projects_discovered,
+                        last_error: w.health.last_error.clone().unwrap_or_else(|| "-".to_string()),
+                    })
+            )
+            .with(Style::modern())
+            .with(Modify::new(Columns::new(4..5)).with(Alignment::right())),
+            if result.active.is_none() {
+                "\nThe daemon is not running; start it with `cargo projects daemon`"
+            } else {
+                ""
+            }
         ),
     }
 }
 
+/// `failed` means the daemon is running but could not watch the path, see the last error.
+fn watcher_state(watcher: &WatcherConfig, active: Option<&HashSet<WatcherName>>) -> &'static str {
+    match active {
+        _ if watcher.paused => "paused",
+        None => "stopped",
+        Some(active) if active.contains(&watcher.name) && watcher.health.polling => "polling",
+        Some(active) if active.contains(&watcher.name) => "watching",
+        Some(_) => "failed",
+    }
+}
+
+fn format_optional_time(time: Option<DateTime<Utc>>) -> String {
+    time.map_or_else(
+        || "-".to_string(),
+        |time| time.format("%Y-%m-%d %H:%M").to_string(),
+    )
+}
+
+pub fn format_watcher_pause_result(watcher: &WatcherConfig) -> String {
+    if watcher.paused {
+        format!("Paused watcher {}", watcher.name)
+    } else {
+        format!("Resumed watcher {}", watcher.name)
+    }
+}
+
+pub fn format_watcher_added(watcher: &WatcherConfig) -> String {
+    format!(
+        "Started watching {} at {}",
+        watcher.name,
+        watcher.path.display()
+    )
+}
+
+pub fn format_watcher_removed(name: &WatcherName) -> String {
+    format!("Removed watcher {name}")
+}
+
+pub fn format_watcher_renamed(old_name: &WatcherName, watcher: &WatcherConfig) -> String {
+    format!("Renamed watcher {old_name} to {}", watcher.name)
+}
+
+pub fn format_watcher_edited(watcher: &WatcherConfig) -> String {
+    format!("Updated watcher {}\n{}", watcher.name, format_watcher_config(watcher))
+}
+
+pub fn format_watcher_details(details: &WatcherDetails) -> String {
+    format!(
+        "{}\n\n{}\nCombined size: {:.3} GB ({:.3} GB cache)",
+        format_watcher_config(&details.watcher),
+        format_project_list(&details.projects, false),
+        details.total_size.as_gb(),
+        details.target_size.as_gb()
+    )
+}
+
+fn format_watcher_config(watcher: &WatcherConfig) -> String {
+    let globs = |globs: &[String]| {
+        if globs.is_empty() {
+            "-".to_string()
+        } else {
+            globs.join(", ")
+        }
+    };
+
+    format!(
+        "Name:            {}{}\nPath:            {}\nCreated:         {}\nInclude:         {}\nExclude:         {}\nMax depth:       {}\nFollow symlinks: {}\nMode:            {}\nPaused:          {}\nLast event:      {}\nDiscovered:      {} projects\nLast error:      {}",
+        watcher.name,
+        if watcher.system_managed { " (system-wide)" } else { "" },
+        watcher.path.display(),
+        watcher.created_at.format("%Y-%m-%d %H:%M"),
+        globs(&watcher.rules.include),
+        globs(&watcher.rules.exclude),
+        watcher
+            .rules
+            .max_depth
+            .map_or_else(|| "default".to_string(), |depth| depth.to_string()),
+        if watcher.rules.follow_symlinks { "yes" } else { "no" },
+        match watcher.mode {
+            WatchMode::Native => "native events".to_string(),
+            WatchMode::Poll { interval_secs } => format!("poll every {interval_secs}s"),
+        },
+        if watcher.paused { "yes" } else { "no" },
+        format_optional_time(watcher.health.last_event_at),
+        watcher.health.projects_discovered,
+        watcher.health.last_error.as_ref().map_or_else(
+            || "-".to_string(),
+            |error| format!("{error} ({})", format_optional_time(watcher.health.last_error_at))
+        )
+    )
+}
+
 pub fn format_scan_result(result: &ScanResult) -> String {
     match result.added_count {
         0 => "No new projects found.".to_string(),
@@ -93,6 +257,28 @@ pub fn format_scan_result(result: &ScanResult) -> String {
     }
 }
 
+pub fn format_system_wide_result(result: &SystemWideResult) -> String {
+    let watchers = if result.watchers.is_empty() {
+        "No directories with Rust projects found.".to_string()
+    } else {
+        format!(
+            "Watching {} directories:\n{}",
+            result.watchers.len(),
+            result
+                .watchers
+                .iter()
+                .map(|w| format!("  • {} ({})", w.name, w.path.display()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        )
+    };
+
+    format!(
+        "{watchers}\nReplaced {} previous system-wide watchers, added {} new projects.",
+        result.removed_watchers, result.added_projects
+    )
+}
+
 pub fn format_update_result(result: &UpdateResult) -> String {
     match result.total_updated {
         0 => "All projects are up to date.".to_string(),
@@ -113,10 +299,327 @@ pub fn format_clean_result(project_name: &str) -> String {
     format!("Cleaned project: {project_name}")
 }
 
+pub fn format_clean_tagged_result(result: &CleanResult) -> String {
+    if result.cleaned.is_empty() && result.skipped.is_empty() && result.failed.is_empty() {
+        return "No project carries these tags.".to_string();
+    }
+
+    let mut output = format!("Cleaned {} projects", result.cleaned.len());
+    for project in &result.cleaned {
+        output.push_str(&format!("\n  • {} {}", project.id, project.name));
+    }
+    if !result.skipped.is_empty() {
+        output.push_str(&format!(
+            "\nSkipped {} pinned projects, pass --force to clean them too:",
+            result.skipped.len()
+        ));
+        for project in &result.skipped {
+            output.push_str(&format!("\n  • {} {}", project.id, project.name));
+        }
+    }
+    if !result.failed.is_empty() {
+        output.push_str(&format!("\nFailed to clean {} projects:", result.failed.len()));
+        for (project, error) in &result.failed {
+            output.push_str(&format!("\n  • {} {}: {error}", project.id, project.name));
+        }
+    }
+    output
+}
+
+pub fn format_tag_result(project: &RustProject) -> String {
+    if project.annotations.tags.is_empty() {
+        format!("Project {} {} has no tags", project.id, project.name)
+    } else {
+        format!("Tagged project {} {}: {}", project.id, project.name, format_tags(project))
+    }
+}
+
+pub fn format_pin_result(project: &RustProject) -> String {
+    let action = if project.annotations.pinned { "Pinned" } else { "Unpinned" };
+    format!("{action} project {} {}", project.id, project.name)
+}
+
+pub fn format_note_result(project: &RustProject) -> String {
+    project.annotations.note.as_ref().map_or_else(
+        || format!("Project {} {} has no note", project.id, project.name),
+        |note| format!("Note on project {} {}:\n{note}", project.id, project.name),
+    )
+}
+
+fn format_tags(project: &RustProject) -> String {
+    project
+        .annotations
+        .tags
+        .iter()
+        .map(Tag::as_str)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn format_prune_result(result: &PruneResult) -> String {
+    if result.pruned.is_empty() {
+        return "Every tracked project still exists.".to_string();
+    }
+
+    let action = if result.dry_run { "Would untrack" } else { "Untracked" };
+    format!(
+        "{action} {} projects:\n{}",
+        result.pruned.len(),
+        result
+            .pruned
+            .iter()
+            .map(|(project, reason)| {
+                let reason = match reason {
+                    PruneReason::Missing => "directory missing",
+                    PruneReason::NotCargo => "no Cargo.toml",
+                };
+                format!(
+                    "  • {} {} ({reason}): {}",
+                    project.id,
+                    project.name,
+                    project.path.display()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    )
+}
+
+pub fn format_remove_result(project: &RustProject) -> String {
+    format!(
+        "Untracked project {} {} ({})",
+        project.id,
+        project.name,
+        project.path.display()
+    )
+}
+
+pub fn format_renumber_result(result: &RenumberResult) -> String {
+    match result.renumbered {
+        0 => "Project IDs are already contiguous.".to_string(),
+        renumbered => format!(
+            "Renumbered {renumbered} of {} projects, IDs now run from 1 to {}",
+            result.total, result.total
+        ),
+    }
+}
+
+/// Columns of a sparkline, longer histories are bucketed to fit
+const SPARKLINE_WIDTH: usize = 60;
+/// Most recent snapshots listed below the sparklines
+const HISTORY_TABLE_ROWS: usize = 10;
+
+#[derive(Tabled)]
+struct SnapshotTableRow {
+    #[tabled(rename = "Recorded")]
+    recorded_at: String,
+    #[tabled(rename = "Size (GB)")]
+    size: String,
+    #[tabled(rename = "Cache (GB)")]
+    cache: String,
+}
+
+pub fn format_history_result(result: &HistoryResult) -> String {
+    let (Some(first), Some(last)) = (result.snapshots.first(), result.snapshots.last()) else {
+        return format!(
+            "No size history for {} yet. Run 'update' to record one.",
+            result.project.name
+        );
+    };
+
+    let sizes: Vec<FileSize> = result.snapshots.iter().map(|s| s.size_bytes).collect();
+    let targets: Vec<FileSize> = result.snapshots.iter().map(|s| s.target_size_bytes).collect();
+    let rows: Vec<SnapshotTableRow> = result
+        .snapshots
+        .iter()
+        .rev()
+        .take(HISTORY_TABLE_ROWS)
+        .rev()
+        .map(|snapshot| SnapshotTableRow {
+            recorded_at: format_optional_time(Some(snapshot.recorded_at)),
+            size: format!("{:.3}", snapshot.size_bytes.as_gb()),
+            cache: format!("{:.3}", snapshot.target_size_bytes.as_gb()),
+        })
+        .collect();
+
+    format!(
+        "{} (ID {}), {} snapshots since {}\n\
+         Size   {}  {:.3} GB -> {:.3} GB\n\
+         Cache  {}  {:.3} GB -> {:.3} GB\n{}",
+        result.project.name,
+        result.project.id,
+        result.snapshots.len(),
+        format_optional_time(Some(first.recorded_at)),
+        sparkline(&sizes, SPARKLINE_WIDTH),
+        first.size_bytes.as_gb(),
+        last.size_bytes.as_gb(),
+        sparkline(&targets, SPARKLINE_WIDTH),
+        first.target_size_bytes.as_gb(),
+        last.target_size_bytes.as_gb(),
+        Table::new(&rows)
+            .with(Style::modern())
+            .with(Modify::new(Columns::new(1..3)).with(Alignment::right())),
+    )
+}
+
+/// Draws `sizes` scaled between their minimum and maximum, using the largest size of each bucket
+/// when there are more sizes than `width`.
+fn sparkline(sizes: &[FileSize], width: usize) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let bytes: Vec<u64> = sizes
+        .chunks(sizes.len().div_ceil(width.max(1)).max(1))
+        .filter_map(|bucket| bucket.iter().map(|size| size.bytes()).max())
+        .collect();
+    let min = bytes.iter().copied().min().unwrap_or(0);
+    let range = u128::from(bytes.iter().copied().max().unwrap_or(0) - min);
+
+    bytes
+        .iter()
+        .map(|&value| match range {
+            0 => BARS[0],
+            _ => BARS[(u128::from(value - min) * (BARS.len() as u128 - 1) / range) as usize],
+        })
+        .collect()
+}
+
+pub fn format_export_result(result: ExportResult) -> String {
+    match result {
+        ExportResult::Printed(content) => content,
+        ExportResult::Written {
+            path,
+            projects,
+            watchers,
+        } => format!(
+            "Exported {projects} projects and {watchers} watchers to {}",
+            path.display()
+        ),
+    }
+}
+
+pub fn format_import_result(result: &ImportResult) -> String {
+    let action = match result.mode {
+        ImportMode::Merge => "Merged",
+        ImportMode::Replace => "Replaced the registry with",
+    };
+    let mut output = format!(
+        "{action} {} projects and {} watchers",
+        result.projects, result.watchers
+    );
+    if result.renumbered > 0 {
+        output.push_str(&format!(
+            "\n{} projects got new IDs because theirs were already taken",
+            result.renumbered
+        ));
+    }
+    output
+}
+
+#[derive(Tabled)]
+struct BackupTableRow {
+    #[tabled(rename = "Timestamp")]
+    timestamp: String,
+    #[tabled(rename = "Taken")]
+    taken_at: String,
+    #[tabled(rename = "Files")]
+    files: String,
+}
+
+pub fn format_backup_list(backups: &[Backup]) -> String {
+    if backups.is_empty() {
+        return "No backups yet.".to_string();
+    }
+
+    let mut rows: Vec<BackupTableRow> = Vec::new();
+    for backup in backups {
+        match rows.last_mut() {
+            Some(row) if row.timestamp == backup.taken_at.to_string() => {
+                row.files.push_str(&format!(", {}", backup.file_name));
+            }
+            _ => rows.push(BackupTableRow {
+                timestamp: backup.taken_at.to_string(),
+                taken_at: format_optional_time(Some(backup.taken_at.get())),
+                files: backup.file_name.clone(),
+            }),
+        }
+    }
+
+    format!(
+        "{}\nRestore with: cargo projects backup restore <timestamp>",
+        Table::new(&rows).with(Style::modern())
+    )
+}
+
+pub fn format_restore_result(timestamp: BackupTimestamp, restored: &[std::path::PathBuf]) -> String {
+    match restored {
+        [] => format!("Nothing changed since {timestamp}."),
+        files => format!(
+            "Restored the state of {timestamp}:\n{}",
+            files
+                .iter()
+                .map(|path| format!("  • {}", path.display()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+    }
+}
+
 pub fn format_clean_watchers_result() -> String {
     "All watchers cleared".to_string()
 }
 
+pub fn format_daemon_result(summary: &DaemonSummary) -> String {
+    format!(
+        "Daemon stopped after processing {} events from {} watched paths",
+        summary.processed_events,
+        summary.watched_paths.len()
+    )
+}
+
+pub fn format_daemon_status(status: &DaemonStatus) -> String {
+    let mut watchers: Vec<_> = status.active_watchers.iter().collect();
+    watchers.sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+
+    format!(
+        "Daemon running (pid {}) since {}, {} events processed\nActive watchers ({}):\n{}",
+        status.pid,
+        status.started_at.format("%Y-%m-%d %H:%M"),
+        status.processed_events,
+        watchers.len(),
+        watchers
+            .iter()
+            .map(|w| format!("  • {} ({})", w.name, w.path.display()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )
+}
+
+pub fn format_daemon_reload_result() -> String {
+    "Daemon reloaded watchers".to_string()
+}
+
+pub fn format_daemon_stop_result() -> String {
+    "Daemon stopped".to_string()
+}
+
+pub fn format_daemon_install_result(result: &InstallResult) -> String {
+    match result {
+        InstallResult::Printed(unit) => unit.trim_end().to_string(),
+        InstallResult::Installed(path) => format!(
+            "Installed and started {}\nFollow its logs with: journalctl --user -u {}",
+            path.display(),
+            path.file_name().unwrap_or_default().to_string_lossy()
+        ),
+    }
+}
+
+pub fn format_daemon_uninstall_result(removed: Option<&std::path::Path>) -> String {
+    removed.map_or_else(
+        || "No systemd unit installed".to_string(),
+        |path| format!("Stopped and removed {}", path.display()),
+    )
+}
+
 pub fn format_refresh_result() -> String {
     "Timing cache cleared. Run 'update' to refresh timing data.".to_string()
 }
@@ -154,14 +657,92 @@ pub fn print_error<W: Write>(writer: &mut W, error: &dyn std::error::Error) -> i
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::*;
+    use std::collections::HashSet;
 
     #[test]
     fn test_format_project_list_empty() {
         let result = ProjectListResult {
             projects: vec![],
             total_count: 0,
+            missing: HashSet::new(),
+        };
+        assert_eq!(format_project_list(&result, false), "No projects found.");
+    }
+
+    #[test]
+    fn test_format_project_list_expands_workspace_members() {
+        let member = |name: &str| WorkspaceMember {
+            name: ProjectName::new(name.to_string()),
+            path: std::path::PathBuf::from(format!("/dev/bevy/crates/{name}")),
+            version: ProjectVersion::new("0.15.0".to_string()),
+            size_bytes: FileSize::new(2_000_000),
+        };
+        let result = ProjectListResult {
+            projects: vec![RustProject {
+                id: ProjectId::new(3),
+                name: ProjectName::new("bevy".to_string()),
+                path: std::path::PathBuf::from("/dev/bevy"),
+                version: ProjectVersion::new("0.15.0".to_string()),
+                created_at: Utc::now(),
+                last_modified: Utc::now(),
+                size_bytes: FileSize::new(5_000_000_000),
+                target_size_bytes: FileSize::new(4_000_000_000),
+                dependencies_count: DependencyCount::new(0),
+                estimated_build_time_seconds: TimingDuration::new(0),
+                project_type: ProjectType::WorkspaceWithPackage,
+                annotations: ProjectAnnotations {
+                    pinned: true,
+                    ..ProjectAnnotations::default()
+                },
+                members: vec![member("bevy_ecs"), member("bevy_render")],
+            }],
+            total_count: 1,
+            missing: HashSet::new(),
+        };
+
+        let collapsed = format_project_list(&result, false);
+        assert!(collapsed.contains("bevy (2 members, pinned)"));
+        assert!(!collapsed.contains("bevy_ecs"));
+
+        let expanded = format_project_list(&result, true);
+        let member_row = expanded.lines().find(|line| line.contains("└ bevy_ecs")).unwrap();
+        assert!(member_row.contains("0.002"));
+        assert!(member_row.contains('-'));
+        assert!(expanded.lines().position(|line| line.contains("└ bevy_render"))
+            > expanded.lines().position(|line| line.contains("└ bevy_ecs")));
+        assert!(expanded.ends_with("Total: 1 projects"));
+    }
+
+    #[test]
+    fn test_watcher_state() {
+        let mut watcher = WatcherConfig {
+            name: WatcherName::new("work".to_string()),
+            path: std::path::PathBuf::from("/home/alice/work"),
+            created_at: Utc::now(),
+            system_managed: false,
+            rules: crate::types::WatchRules::default(),
+            mode: WatchMode::Native,
+            paused: false,
+            health: crate::types::WatcherHealth::default(),
         };
-        assert_eq!(format_project_list(&result), "No projects found.");
+        let active = HashSet::from([watcher.name.clone()]);
+
+        assert_eq!(watcher_state(&watcher, None), "stopped");
+        assert_eq!(watcher_state(&watcher, Some(&active)), "watching");
+        assert_eq!(watcher_state(&watcher, Some(&HashSet::new())), "failed");
+        watcher.paused = true;
+        assert_eq!(watcher_state(&watcher, Some(&active)), "paused");
+    }
+
+    #[test]
+    fn test_sparkline() {
+        let sizes: Vec<FileSize> = [0, 7, 14, 14].into_iter().map(FileSize::new).collect();
+
+        assert_eq!(sparkline(&sizes, 10), "▁▄██");
+        assert_eq!(sparkline(&sizes, 2), "▁█");
+        assert_eq!(sparkline(&sizes[1..2], 10), "▁");
+        assert_eq!(sparkline(&[], 10), "");
     }
 
     #[test]
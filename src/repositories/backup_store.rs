@@ -0,0 +1,192 @@
+use crate::infrastructure::FileSystem;
+use crate::types::*;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Where the RON files are backed up before being overwritten, and how many backups are kept
+#[derive(Debug, Clone)]
+pub struct BackupSettings {
+    pub dir: PathBuf,
+    /// Backups kept per file, 0 disables backups
+    pub keep: usize,
+    /// Writes closer than this to the newest backup of a file are not backed up again
+    pub interval: chrono::TimeDelta,
+}
+
+/// A copy of a file as it was just before the write at `taken_at`
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub file_name: String,
+    pub taken_at: BackupTimestamp,
+    pub path: PathBuf,
+}
+
+pub struct BackupStore<F: FileSystem> {
+    file_system: Arc<F>,
+    settings: BackupSettings,
+}
+
+impl<F: FileSystem> BackupStore<F> {
+    pub const fn new(file_system: Arc<F>, settings: BackupSettings) -> Self {
+        Self {
+            file_system,
+            settings,
+        }
+    }
+
+    /// Copies `path` into the backup directory before it is overwritten, unless its newest backup
+    /// is younger than the interval, and drops its oldest backups beyond `keep`.
+    pub fn back_up(&self, path: &Path) -> ProjectsResult<()> {
+        let now = BackupTimestamp::now();
+        let is_recent = self
+            .backups_of(path)?
+            .last()
+            .is_some_and(|newest| now.get() - newest.taken_at.get() < self.settings.interval);
+        if is_recent {
+            return Ok(());
+        }
+        self.take(path, now)
+    }
+
+    /// Every backup, oldest first.
+    pub fn list(&self) -> ProjectsResult<Vec<Backup>> {
+        let mut backups: Vec<Backup> = self
+            .file_system
+            .read_dir(&self.settings.dir)?
+            .into_iter()
+            .filter_map(parse_backup)
+            .collect();
+        backups.sort_by(|a, b| (a.taken_at, &a.file_name).cmp(&(b.taken_at, &b.file_name)));
+        Ok(backups)
+    }
+
+    /// Rolls every file in `paths` back to its newest backup taken at or before `timestamp`. Files
+    /// without such a backup are left alone, and the current content of every restored file is
+    /// backed up first. Returns the restored files.
+    pub fn restore(&self, timestamp: BackupTimestamp, paths: &[PathBuf]) -> ProjectsResult<Vec<PathBuf>> {
+        let backups = self.list()?;
+        let chosen: Vec<(&PathBuf, &Backup)> = paths
+            .iter()
+            .filter_map(|path| {
+                backups
+                    .iter()
+                    .rev()
+                    .find(|backup| {
+                        backup.taken_at <= timestamp && Some(backup.file_name.as_str()) == file_name(path)
+                    })
+                    .map(|backup| (path, backup))
+            })
+            .collect();
+        if chosen.is_empty() {
+            return Err(ProjectsError::BackupNotFound { timestamp });
+        }
+
+        let mut restored = Vec::new();
+        for (path, backup) in chosen {
+            let _lock = self.file_system.lock(path)?;
+            let content = self.file_system.read_to_string(&backup.path)?;
+            self.take(path, BackupTimestamp::now())?;
+            self.file_system.write(path, &content)?;
+            restored.push(path.clone());
+        }
+        Ok(restored)
+    }
+
+    fn take(&self, path: &Path, taken_at: BackupTimestamp) -> ProjectsResult<()> {
+        let Some(name) = file_name(path) else {
+            return Ok(());
+        };
+        if self.settings.keep == 0 || !self.file_system.exists(path) {
+            return Ok(());
+        }
+
+        // Within the same second the backup already taken is the older state, so it is kept.
+        let backup = self.settings.dir.join(format!("{name}.{taken_at}"));
+        if self.file_system.exists(&backup) {
+            return Ok(());
+        }
+        let content = self.file_system.read_to_string(path)?;
+        self.file_system.write(&backup, &content)?;
+
+        let backups = self.backups_of(path)?;
+        for old in &backups[..backups.len().saturating_sub(self.settings.keep)] {
+            self.file_system.remove_file(&old.path)?;
+        }
+        Ok(())
+    }
+
+    /// Backups of one file, oldest first.
+    fn backups_of(&self, path: &Path) -> ProjectsResult<Vec<Backup>> {
+        let name = file_name(path);
+        self.list().map(|backups| {
+            backups
+                .into_iter()
+                .filter(|backup| Some(backup.file_name.as_str()) == name)
+                .collect()
+        })
+    }
+}
+
+fn file_name(path: &Path) -> Option<&str> {
+    path.file_name().and_then(|name| name.to_str())
+}
+
+/// `registry.ron.20261017T171717Z` -> a backup of `registry.ron`.
+fn parse_backup(path: PathBuf) -> Option<Backup> {
+    let (file_name, timestamp) = file_name(&path)?.rsplit_once('.')?;
+    Some(Backup {
+        file_name: file_name.to_string(),
+        taken_at: timestamp.parse().ok()?,
+        path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::RealFileSystem;
+
+    #[test]
+    fn test_backups_rotate_and_restore() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let registry = temp_dir.path().join("registry.ron");
+        let store = BackupStore::new(
+            Arc::new(RealFileSystem),
+            BackupSettings {
+                dir: temp_dir.path().join("backups"),
+                keep: 2,
+                interval: chrono::TimeDelta::zero(),
+            },
+        );
+
+        store.back_up(&registry)?;
+        assert_eq!(store.list()?.len(), 0);
+
+        for (taken_at, content) in [
+            ("20260101T000000Z", "first"),
+            ("20260101T000001Z", "second"),
+            ("20260101T000002Z", "third"),
+        ] {
+            RealFileSystem.write(&registry, content)?;
+            store.take(&registry, taken_at.parse()?)?;
+        }
+        RealFileSystem.write(&registry, "current")?;
+
+        let backups = store.list()?;
+        assert_eq!(backups.len(), 2);
+        assert_eq!(RealFileSystem.read_to_string(&backups[0].path)?, "second");
+
+        assert_eq!(store.restore(backups[0].taken_at, std::slice::from_ref(&registry))?, vec![registry.clone()]);
+        assert_eq!(RealFileSystem.read_to_string(&registry)?, "second");
+        assert_eq!(RealFileSystem.read_to_string(&store.list()?.last().unwrap().path)?, "current");
+
+        // The backup taken by the restore holds writes made after the requested time
+        assert_eq!(store.restore("20260101T000005Z".parse()?, std::slice::from_ref(&registry))?, vec![registry.clone()]);
+        assert_eq!(RealFileSystem.read_to_string(&registry)?, "third");
+        assert!(matches!(
+            store.restore("20260101T000000Z".parse()?, std::slice::from_ref(&registry)),
+            Err(ProjectsError::BackupNotFound { .. })
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,267 @@
+use crate::repositories::ProjectRepository;
+use crate::types::*;
+use log::warn;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Unit of work over another repository for the lifetime of one command.
+///
+/// Projects are read once, and saves of tracked projects are held back and written in one go by
+/// `flush`. Saves of new paths are written right away because only the underlying repository can
+/// hand out their ids.
+///
+/// Not meant for the daemon: its cache would miss changes made by the CLI.
+pub struct CachedProjectRepository {
+    inner: Arc<dyn ProjectRepository>,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    /// `None` until the first read, and again after writes the cache cannot follow
+    registry: Option<ProjectRegistry>,
+    pending: Vec<RustProject>,
+}
+
+impl CachedProjectRepository {
+    pub fn new(inner: Arc<dyn ProjectRepository>) -> Self {
+        Self {
+            inner,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn read<T>(&self, read: impl FnOnce(&ProjectRegistry) -> T) -> ProjectsResult<T> {
+        let mut state = self.state();
+        if state.registry.is_none() {
+            let mut registry = ProjectRegistry::new();
+            registry.projects = self
+                .inner
+                .find_all()?
+                .into_iter()
+                .map(|project| (project.path.clone(), project))
+                .collect();
+            state.registry = Some(registry);
+        }
+        Ok(read(state.registry.as_ref().expect("registry was just loaded")))
+    }
+
+    /// Writes the held back saves, then forgets the cache so the next read sees what a
+    /// non-caching write is about to change.
+    fn flush_and_invalidate(&self) -> ProjectsResult<()> {
+        self.flush()?;
+        self.state().registry = None;
+        Ok(())
+    }
+
+    /// Holds the save back when the path is tracked, returning its id, or `None` for a new path.
+    fn defer_save(&self, project: &RustProject) -> ProjectsResult<Option<ProjectId>> {
+        let tracked = self.read(|registry| {
+            registry
+                .projects
+                .get(&project.path)
+                .map(|tracked| (tracked.id, tracked.annotations.clone()))
+        })?;
+        let Some((id, annotations)) = tracked else {
+            return Ok(None);
+        };
+
+        let project = RustProject {
+            id,
+            annotations,
+            ..project.clone()
+        };
+        let mut state = self.state();
+        if let Some(registry) = state.registry.as_mut() {
+            registry.projects.insert(project.path.clone(), project.clone());
+        }
+        state.pending.push(project);
+        drop(state);
+        Ok(Some(id))
+    }
+
+    fn cache_saved(&self, projects: Vec<RustProject>) {
+        if let Some(registry) = self.state().registry.as_mut() {
+            for project in projects {
+                registry.projects.insert(project.path.clone(), project);
+            }
+        }
+    }
+}
+
+impl ProjectRepository for CachedProjectRepository {
+    fn find_all(&self) -> ProjectsResult<Vec<RustProject>> {
+        self.read(|registry| registry.projects.values().cloned().collect())
+    }
+
+    fn find_by_id(&self, id: ProjectId) -> ProjectsResult<Option<RustProject>> {
+        self.read(|registry| registry.projects.values().find(|p| p.id == id).cloned())
+    }
+
+    fn find_by_watcher(&self, watcher_name: &WatcherName) -> ProjectsResult<Vec<RustProject>> {
+        self.flush()?;
+        self.inner.find_by_watcher(watcher_name)
+    }
+
+    fn find_by_path(&self, path: &Path) -> ProjectsResult<Option<RustProject>> {
+        let canonical_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.read(|registry| registry.projects.get(&canonical_path).cloned())
+    }
+
+    fn find_containing_project(&self, path: &Path) -> ProjectsResult<Option<RustProject>> {
+        self.read(|registry| registry.find_project_containing_path(path).cloned())
+    }
+
+    fn save(&self, project: RustProject) -> ProjectsResult<ProjectId> {
+        if let Some(id) = self.defer_save(&project)? {
+            return Ok(id);
+        }
+        let id = self.inner.save(project.clone())?;
+        self.cache_saved(vec![RustProject { id, ..project }]);
+        Ok(id)
+    }
+
+    fn save_all(&self, projects: Vec<RustProject>) -> ProjectsResult<Vec<ProjectId>> {
+        let mut ids = Vec::with_capacity(projects.len());
+        let mut new_projects = Vec::new();
+        for project in projects {
+            let id = self.defer_save(&project)?;
+            if id.is_none() {
+                new_projects.push(project);
+            }
+            ids.push(id);
+        }
+        if new_projects.is_empty() {
+            return Ok(ids.into_iter().flatten().collect());
+        }
+
+        let mut new_ids = self.inner.save_all(new_projects.clone())?.into_iter();
+        self.cache_saved(
+            new_projects
+                .into_iter()
+                .zip(new_ids.clone())
+                .map(|(project, id)| RustProject { id, ..project })
+                .collect(),
+        );
+        Ok(ids
+            .into_iter()
+            .map(|id| id.or_else(|| new_ids.next()).expect("one id per saved project"))
+            .collect())
+    }
+
+    fn remove(&self, id: ProjectId) -> ProjectsResult<bool> {
+        self.flush_and_invalidate()?;
+        self.inner.remove(id)
+    }
+
+    fn remove_all(&self, ids: &[ProjectId]) -> ProjectsResult<Vec<ProjectId>> {
+        self.flush_and_invalidate()?;
+        self.inner.remove_all(ids)
+    }
+
+    fn relocate(&self, from: &Path, to: &Path) -> ProjectsResult<usize> {
+        self.flush_and_invalidate()?;
+        self.inner.relocate(from, to)
+    }
+
+    fn annotate(
+        &self,
+        id: ProjectId,
+        edit: &dyn Fn(&mut ProjectAnnotations),
+    ) -> ProjectsResult<Option<RustProject>> {
+        self.flush_and_invalidate()?;
+        self.inner.annotate(id, edit)
+    }
+
+    fn renumber(&self) -> ProjectsResult<Vec<(ProjectId, ProjectId)>> {
+        self.flush_and_invalidate()?;
+        self.inner.renumber()
+    }
+
+    fn import(&self, projects: Vec<RustProject>, mode: ImportMode) -> ProjectsResult<usize> {
+        self.flush_and_invalidate()?;
+        self.inner.import(projects, mode)
+    }
+
+    fn exists(&self, path: &Path) -> ProjectsResult<bool> {
+        self.find_by_path(path).map(|project| project.is_some())
+    }
+
+    fn remove_all_projects(&self) -> ProjectsResult<bool> {
+        self.flush_and_invalidate()?;
+        self.inner.remove_all_projects()
+    }
+
+    fn flush(&self) -> ProjectsResult<()> {
+        let pending = std::mem::take(&mut self.state().pending);
+        if pending.is_empty() {
+            return Ok(());
+        }
+        self.inner.save_all(pending).map(|_| ())
+    }
+}
+
+impl Drop for CachedProjectRepository {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!("Failed to write held back project updates: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::RealFileSystem;
+    use crate::repositories::FileProjectRepository;
+    use std::path::PathBuf;
+
+    fn project(path: &str) -> RustProject {
+        RustProject {
+            id: ProjectId::new(0),
+            name: ProjectName::new(path.rsplit('/').next().unwrap_or(path).to_string()),
+            path: PathBuf::from(path),
+            version: ProjectVersion::new("0.1.0".to_string()),
+            created_at: chrono::Utc::now(),
+            last_modified: chrono::Utc::now(),
+            size_bytes: FileSize::new(0),
+            target_size_bytes: FileSize::new(0),
+            dependencies_count: DependencyCount::new(0),
+            estimated_build_time_seconds: TimingDuration::new(0),
+            project_type: ProjectType::Package,
+            annotations: ProjectAnnotations::default(),
+            members: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_updates_are_written_on_flush() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let file = Arc::new(FileProjectRepository::new(
+            Arc::new(RealFileSystem),
+            temp_dir.path().join("registry.ron"),
+            temp_dir.path().join("watchers.ron"),
+        ));
+        let cached = CachedProjectRepository::new(file.clone());
+
+        assert_eq!(cached.save_all(vec![project("/dev/a"), project("/dev/b")])?.len(), 2);
+        assert_eq!(file.find_all()?.len(), 2);
+
+        let updated = RustProject {
+            size_bytes: FileSize::new(42),
+            ..project("/dev/b")
+        };
+        let ids = cached.save_all(vec![updated, project("/dev/c")])?;
+        assert_eq!(ids, [ProjectId::new(2), ProjectId::new(3)]);
+        assert_eq!(cached.find_by_id(ProjectId::new(2))?.unwrap().size_bytes, FileSize::new(42));
+        assert_eq!(file.find_by_id(ProjectId::new(2))?.unwrap().size_bytes, FileSize::new(0));
+
+        cached.flush()?;
+        assert_eq!(file.find_by_id(ProjectId::new(2))?.unwrap().size_bytes, FileSize::new(42));
+        Ok(())
+    }
+}
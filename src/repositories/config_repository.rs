@@ -3,6 +3,7 @@ use std::sync::Arc;
 use crate::types::*;
 use crate::services::AppConfig;
 use crate::infrastructure::FileSystem;
+use crate::repositories::schema;
 use ron::ser::{to_string_pretty, PrettyConfig};
 
 pub trait ConfigRepository: Send + Sync {
@@ -24,15 +25,19 @@ impl<F: FileSystem> FileConfigRepository<F> {
         }
     }
 
+    /// Loads the config, rewriting the file right away when it had to be upgraded.
     fn load_config(&self) -> ProjectsResult<AppConfig> {
-        match self.file_system.exists(&self.config_path) {
-            true => self
-                .file_system
-                .read_to_string(&self.config_path)
-                .map_err(Into::into)
-                .and_then(|content| ron::from_str(&content).map_err(Into::into)),
-            false => Ok(AppConfig::default()),
+        if !self.file_system.exists(&self.config_path) {
+            return Ok(AppConfig::default());
         }
+
+        let content = self.file_system.read_to_string(&self.config_path)?;
+        let loaded = schema::CONFIG.parse(&self.config_path, &content)?;
+        if loaded.is_upgraded() {
+            loaded.back_up(self.file_system.as_ref(), &self.config_path)?;
+            self.save_config(&loaded.value)?;
+        }
+        Ok(loaded.value)
     }
 
     fn save_config(&self, config: &AppConfig) -> ProjectsResult<()> {
@@ -0,0 +1,90 @@
+use crate::infrastructure::FileSystem;
+use crate::repositories::schema::{self, Loaded};
+use crate::types::*;
+use ron::ser::{PrettyConfig, to_string_pretty};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub trait HistoryRepository: Send + Sync {
+    /// Snapshots of a project, oldest first.
+    fn find_by_project(&self, id: ProjectId) -> ProjectsResult<Vec<SizeSnapshot>>;
+    /// Appends a snapshot per project, see `SizeSnapshot::append_to`.
+    fn record(&self, snapshots: Vec<(ProjectId, SizeSnapshot)>) -> ProjectsResult<()>;
+    fn remove(&self, ids: &[ProjectId]) -> ProjectsResult<()>;
+    /// Moves snapshots from the old to the new id of each renumbered project.
+    fn renumber(&self, changes: &[(ProjectId, ProjectId)]) -> ProjectsResult<()>;
+    fn remove_all(&self) -> ProjectsResult<()>;
+}
+
+pub struct FileHistoryRepository<F: FileSystem> {
+    file_system: Arc<F>,
+    history_path: PathBuf,
+}
+
+impl<F: FileSystem> FileHistoryRepository<F> {
+    pub const fn new(file_system: Arc<F>, history_path: PathBuf) -> Self {
+        Self {
+            file_system,
+            history_path,
+        }
+    }
+
+    pub fn load_history(&self) -> ProjectsResult<SizeHistory> {
+        self.read_history().map(|loaded| loaded.value)
+    }
+
+    fn read_history(&self) -> ProjectsResult<Loaded<SizeHistory>> {
+        if !self.file_system.exists(&self.history_path) {
+            return Ok(Loaded::current(SizeHistory::new()));
+        }
+        self.file_system
+            .read_to_string(&self.history_path)
+            .and_then(|content| schema::SIZE_HISTORY.parse(&self.history_path, &content))
+    }
+
+    /// Runs a load/modify/save cycle under the history lock, always saving the result.
+    fn update_history(&self, update: impl FnOnce(&mut SizeHistory)) -> ProjectsResult<()> {
+        let _lock = self.file_system.lock(&self.history_path)?;
+        let mut loaded = self.read_history()?;
+        loaded.back_up(self.file_system.as_ref(), &self.history_path)?;
+        update(&mut loaded.value);
+        self.save_history(&loaded.value)
+    }
+
+    fn save_history(&self, history: &SizeHistory) -> ProjectsResult<()> {
+        to_string_pretty(history, PrettyConfig::default())
+            .map_err(Into::into)
+            .and_then(|content| self.file_system.write(&self.history_path, &content))
+    }
+}
+
+impl<F: FileSystem> HistoryRepository for FileHistoryRepository<F> {
+    fn find_by_project(&self, id: ProjectId) -> ProjectsResult<Vec<SizeSnapshot>> {
+        self.load_history()
+            .map(|mut history| history.projects.remove(&id).unwrap_or_default())
+    }
+
+    fn record(&self, snapshots: Vec<(ProjectId, SizeSnapshot)>) -> ProjectsResult<()> {
+        self.update_history(|history| {
+            for (id, snapshot) in snapshots {
+                snapshot.append_to(history.projects.entry(id).or_default());
+            }
+        })
+    }
+
+    fn remove(&self, ids: &[ProjectId]) -> ProjectsResult<()> {
+        self.update_history(|history| {
+            for id in ids {
+                history.projects.remove(id);
+            }
+        })
+    }
+
+    fn renumber(&self, changes: &[(ProjectId, ProjectId)]) -> ProjectsResult<()> {
+        self.update_history(|history| history.renumber(changes))
+    }
+
+    fn remove_all(&self) -> ProjectsResult<()> {
+        self.update_history(|history| history.projects.clear())
+    }
+}
@@ -1,7 +1,18 @@
 pub mod project_repository;
 pub mod watcher_repository;
 pub mod config_repository;
+pub mod backup_store;
+pub mod cached_repository;
+pub mod history_repository;
+pub mod sqlite_repository;
+pub mod schema;
 
 pub use project_repository::{ProjectRepository, FileProjectRepository};
 pub use watcher_repository::{WatcherRepository, FileWatcherRepository};
-pub use config_repository::{ConfigRepository, FileConfigRepository};
\ No newline at end of file
+pub use config_repository::{ConfigRepository, FileConfigRepository};
+pub use backup_store::{Backup, BackupSettings, BackupStore};
+pub use cached_repository::CachedProjectRepository;
+pub use history_repository::{HistoryRepository, FileHistoryRepository};
+pub use sqlite_repository::{
+    SqliteDatabase, SqliteHistoryRepository, SqliteProjectRepository, SqliteWatcherRepository,
+};
@@ -2,6 +2,8 @@ use std::path::Path;
 use std::sync::Arc;
 use crate::types::*;
 use crate::infrastructure::FileSystem;
+use crate::repositories::BackupStore;
+use crate::repositories::schema::{self, Loaded};
 use ron::ser::{to_string_pretty, PrettyConfig};
 
 pub trait ProjectRepository: Send + Sync {
@@ -10,18 +12,39 @@ pub trait ProjectRepository: Send + Sync {
     fn find_by_watcher(&self, watcher_name: &WatcherName) -> ProjectsResult<Vec<RustProject>>;
     fn find_by_path(&self, path: &Path) -> ProjectsResult<Option<RustProject>>;
     fn find_containing_project(&self, path: &Path) -> ProjectsResult<Option<RustProject>>;
-    fn save(&self, project: RustProject) -> ProjectsResult<()>;
-    fn save_all(&self, projects: Vec<RustProject>) -> ProjectsResult<()>;
-    #[allow(dead_code)]
+    /// Inserts or replaces the project at its path, returning the id it is stored under. A tracked
+    /// path keeps its annotations, only `annotate` and `import` change them.
+    fn save(&self, project: RustProject) -> ProjectsResult<ProjectId>;
+    fn save_all(&self, projects: Vec<RustProject>) -> ProjectsResult<Vec<ProjectId>>;
     fn remove(&self, id: ProjectId) -> ProjectsResult<bool>;
+    /// Removes the projects in one write, returning the ids that were tracked.
+    fn remove_all(&self, ids: &[ProjectId]) -> ProjectsResult<Vec<ProjectId>>;
+    fn relocate(&self, from: &Path, to: &Path) -> ProjectsResult<usize>;
+    /// Lets `edit` change the annotations of a project, returning the edited project or `None`
+    /// when no project has the id.
+    fn annotate(
+        &self,
+        id: ProjectId,
+        edit: &dyn Fn(&mut ProjectAnnotations),
+    ) -> ProjectsResult<Option<RustProject>>;
+    /// Gives the projects contiguous ids from 1 in their current order, returning the old and new
+    /// id of every project whose id changed.
+    fn renumber(&self) -> ProjectsResult<Vec<(ProjectId, ProjectId)>>;
+    /// Adds projects from another registry, returning how many could not keep their imported id.
+    fn import(&self, projects: Vec<RustProject>, mode: ImportMode) -> ProjectsResult<usize>;
     fn exists(&self, path: &Path) -> ProjectsResult<bool>;
     fn remove_all_projects(&self) -> ProjectsResult<bool>;
+    /// Writes saves that were held back, a no-op for repositories that write right away.
+    fn flush(&self) -> ProjectsResult<()> {
+        Ok(())
+    }
 }
 
 pub struct FileProjectRepository<F: FileSystem> {
     file_system: Arc<F>,
     registry_path: std::path::PathBuf,
     watcher_registry_path: std::path::PathBuf,
+    backups: Option<Arc<BackupStore<F>>>,
 }
 
 impl<F: FileSystem> FileProjectRepository<F> {
@@ -34,21 +57,50 @@ impl<F: FileSystem> FileProjectRepository<F> {
             file_system,
             registry_path,
             watcher_registry_path,
+            backups: None,
         }
     }
 
+    /// Backs the registry up before it is overwritten.
+    #[must_use]
+    pub fn with_backups(mut self, backups: Arc<BackupStore<F>>) -> Self {
+        self.backups = Some(backups);
+        self
+    }
+
     fn load_registry(&self) -> ProjectsResult<ProjectRegistry> {
+        self.read_registry().map(|loaded| loaded.value)
+    }
+
+    fn read_registry(&self) -> ProjectsResult<Loaded<ProjectRegistry>> {
         match self.file_system.exists(&self.registry_path) {
             true => self
                 .file_system
                 .read_to_string(&self.registry_path)
-                .map_err(Into::into)
-                .and_then(|content| ron::from_str(&content).map_err(Into::into)),
-            false => Ok(ProjectRegistry::new()),
+                .and_then(|content| schema::PROJECT_REGISTRY.parse(&self.registry_path, &content)),
+            false => Ok(Loaded::current(ProjectRegistry::new())),
         }
     }
 
+    /// Runs a load/modify/save cycle under the registry lock so concurrent writers cannot lose
+    /// each other's updates.
+    fn with_locked_registry<T>(
+        &self,
+        update: impl FnOnce(ProjectRegistry) -> ProjectsResult<T>,
+    ) -> ProjectsResult<T> {
+        let _lock = self.file_system.lock(&self.registry_path)?;
+        let loaded = self.read_registry()?;
+        if loaded.is_upgraded() {
+            loaded.back_up(self.file_system.as_ref(), &self.registry_path)?;
+            self.save_registry(&loaded.value)?;
+        }
+        update(loaded.value)
+    }
+
     fn save_registry(&self, registry: &ProjectRegistry) -> ProjectsResult<()> {
+        if let Some(backups) = &self.backups {
+            backups.back_up(&self.registry_path)?;
+        }
         to_string_pretty(registry, PrettyConfig::default())
             .map_err(Into::into)
             .and_then(|content| self.file_system.write(&self.registry_path, &content))
@@ -59,8 +111,8 @@ impl<F: FileSystem> FileProjectRepository<F> {
             true => self
                 .file_system
                 .read_to_string(&self.watcher_registry_path)
-                .map_err(Into::into)
-                .and_then(|content| ron::from_str(&content).map_err(Into::into)),
+                .and_then(|content| schema::WATCHER_REGISTRY.parse(&self.watcher_registry_path, &content))
+                .map(|loaded| loaded.value),
             false => Ok(WatcherRegistry::new()),
         }
     }
@@ -106,22 +158,25 @@ impl<F: FileSystem> ProjectRepository for FileProjectRepository<F> {
         Ok(registry.find_project_containing_path(path).cloned())
     }
 
-    fn save(&self, project: RustProject) -> ProjectsResult<()> {
-        self.load_registry().and_then(| mut registry| {
-            registry.add_project(project);
-            self.save_registry(&registry)
+    fn save(&self, project: RustProject) -> ProjectsResult<ProjectId> {
+        self.with_locked_registry(|mut registry| {
+            let id = registry.add_project(project);
+            self.save_registry(&registry).map(|()| id)
         })
     }
 
-    fn save_all(&self, projects: Vec<RustProject>) -> ProjectsResult<()> {
-        self.load_registry().and_then(|mut registry| {
-            projects.into_iter().for_each(|project| registry.add_project(project));
-            self.save_registry(&registry)
+    fn save_all(&self, projects: Vec<RustProject>) -> ProjectsResult<Vec<ProjectId>> {
+        self.with_locked_registry(|mut registry| {
+            let ids = projects
+                .into_iter()
+                .map(|project| registry.add_project(project))
+                .collect();
+            self.save_registry(&registry).map(|()| ids)
         })
     }
 
     fn remove(&self, id: ProjectId) -> ProjectsResult<bool> {
-        self.load_registry().and_then(|mut registry| {
+        self.with_locked_registry(|mut registry| {
             registry.projects
                 .iter()
                 .find(|(_, project)| project.id == id)
@@ -133,6 +188,68 @@ impl<F: FileSystem> ProjectRepository for FileProjectRepository<F> {
         })
     }
 
+    fn remove_all(&self, ids: &[ProjectId]) -> ProjectsResult<Vec<ProjectId>> {
+        self.with_locked_registry(|mut registry| {
+            let mut removed = Vec::new();
+            registry.projects.retain(|_, project| {
+                let remove = ids.contains(&project.id);
+                if remove {
+                    removed.push(project.id);
+                }
+                !remove
+            });
+            if removed.is_empty() {
+                return Ok(removed);
+            }
+            self.save_registry(&registry).map(|()| removed)
+        })
+    }
+
+    fn relocate(&self, from: &Path, to: &Path) -> ProjectsResult<usize> {
+        self.with_locked_registry(|mut registry| {
+            match registry.relocate_projects(from, to)? {
+                0 => Ok(0),
+                moved => self.save_registry(&registry).map(|()| moved),
+            }
+        })
+    }
+
+    fn annotate(
+        &self,
+        id: ProjectId,
+        edit: &dyn Fn(&mut ProjectAnnotations),
+    ) -> ProjectsResult<Option<RustProject>> {
+        self.with_locked_registry(|mut registry| {
+            registry.annotate(id, edit).map_or_else(
+                || Ok(None),
+                |project| self.save_registry(&registry).map(|()| Some(project)),
+            )
+        })
+    }
+
+    fn renumber(&self) -> ProjectsResult<Vec<(ProjectId, ProjectId)>> {
+        self.with_locked_registry(|mut registry| {
+            let changed = registry.renumber();
+            self.save_registry(&registry).map(|()| changed)
+        })
+    }
+
+    fn import(&self, projects: Vec<RustProject>, mode: ImportMode) -> ProjectsResult<usize> {
+        self.with_locked_registry(|mut registry| {
+            if mode == ImportMode::Replace {
+                registry = ProjectRegistry::new();
+            }
+
+            let mut renumbered = 0;
+            for project in projects {
+                if registry.merge_project(project) {
+                    renumbered += 1;
+                }
+            }
+            self.save_registry(&registry).map(|()| renumbered)
+        })
+    }
+
     fn exists(&self, path: &Path) -> ProjectsResult<bool> {
         self.load_registry()
             .map(|registry| {
@@ -142,7 +259,7 @@ impl<F: FileSystem> ProjectRepository for FileProjectRepository<F> {
     }
 
     fn remove_all_projects(&self) -> ProjectsResult<bool> {
-        self.load_registry().and_then(|mut registry| {
+        self.with_locked_registry(|mut registry| {
             registry.projects.clear();
             self.save_registry(&registry).map(|_| true)
         })
@@ -0,0 +1,189 @@
+use crate::infrastructure::FileSystem;
+use crate::services::{AppConfig, CONFIG_VERSION};
+use crate::types::*;
+use ron::ser::{PrettyConfig, to_string_pretty};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+
+/// Upgrades the RON text of a file by one schema version.
+pub type Migration = fn(&str) -> ProjectsResult<String>;
+
+/// The migrations of one kind of file, `migrations[n]` turns version `n` into `n + 1`.
+pub struct Schema {
+    migrations: &'static [Migration],
+}
+
+pub const PROJECT_REGISTRY: Schema = Schema {
+    migrations: &[add_version::<ProjectRegistry>],
+};
+
+pub const WATCHER_REGISTRY: Schema = Schema {
+    migrations: &[add_version::<WatcherRegistry>],
+};
+
+pub const SIZE_HISTORY: Schema = Schema {
+    migrations: &[add_version::<SizeHistory>],
+};
+
+pub const CONFIG: Schema = Schema {
+    migrations: &[add_version::<AppConfig>],
+};
+
+const _: () = assert!(PROJECT_REGISTRY.version() == PROJECT_REGISTRY_VERSION);
+const _: () = assert!(WATCHER_REGISTRY.version() == WATCHER_REGISTRY_VERSION);
+const _: () = assert!(SIZE_HISTORY.version() == SIZE_HISTORY_VERSION);
+const _: () = assert!(CONFIG.version() == CONFIG_VERSION);
+
+#[derive(Deserialize)]
+struct SchemaVersion {
+    #[serde(default)]
+    schema_version: u32,
+}
+
+/// A parsed file, together with its original text when it had to be upgraded.
+pub struct Loaded<T> {
+    pub value: T,
+    upgraded_from: Option<(u32, String)>,
+}
+
+impl Schema {
+    pub const fn version(&self) -> u32 {
+        self.migrations.len() as u32
+    }
+
+    /// Parses `content` read from `path`, running the migrations it is missing first.
+    pub fn parse<T: DeserializeOwned>(&self, path: &Path, content: &str) -> ProjectsResult<Loaded<T>> {
+        let found = ron::from_str::<SchemaVersion>(content)?.schema_version;
+        if found > self.version() {
+            return Err(ProjectsError::SchemaTooNew {
+                path: path.to_path_buf(),
+                found,
+                supported: self.version(),
+            });
+        }
+
+        let mut upgraded = content.to_string();
+        for migration in &self.migrations[found as usize..] {
+            upgraded = migration(&upgraded)?;
+        }
+
+        Ok(Loaded {
+            value: ron::from_str(&upgraded)?,
+            upgraded_from: (found < self.version()).then(|| (found, content.to_string())),
+        })
+    }
+}
+
+impl<T> Loaded<T> {
+    /// A value that is already at the current version, e.g. a fresh registry.
+    pub const fn current(value: T) -> Self {
+        Self {
+            value,
+            upgraded_from: None,
+        }
+    }
+
+    pub const fn is_upgraded(&self) -> bool {
+        self.upgraded_from.is_some()
+    }
+
+    /// Keeps the file as it was before the upgrade next to it, as `<file>.v<version>.bak`.
+    pub fn back_up<F: FileSystem>(&self, file_system: &F, path: &Path) -> ProjectsResult<()> {
+        let Some((version, original)) = &self.upgraded_from else {
+            return Ok(());
+        };
+        let backup = backup_path(path, *version);
+        if file_system.exists(&backup) {
+            return Ok(());
+        }
+        file_system.write(&backup, original)
+    }
+}
+
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".v{version}.bak"));
+    path.with_file_name(file_name)
+}
+
+trait Versioned {
+    fn set_schema_version(&mut self, version: u32);
+}
+
+impl Versioned for ProjectRegistry {
+    fn set_schema_version(&mut self, version: u32) {
+        self.schema_version = version;
+    }
+}
+
+impl Versioned for WatcherRegistry {
+    fn set_schema_version(&mut self, version: u32) {
+        self.schema_version = version;
+    }
+}
+
+impl Versioned for SizeHistory {
+    fn set_schema_version(&mut self, version: u32) {
+        self.schema_version = version;
+    }
+}
+
+impl Versioned for AppConfig {
+    fn set_schema_version(&mut self, version: u32) {
+        self.schema_version = version;
+    }
+}
+
+/// Version 0 files predate `schema_version`, every field added since has a serde default.
+fn add_version<T: DeserializeOwned + serde::Serialize + Versioned>(content: &str) -> ProjectsResult<String> {
+    let mut value: T = ron::from_str(content)?;
+    value.set_schema_version(1);
+    to_string_pretty(&value, PrettyConfig::default()).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::RealFileSystem;
+
+    const UNVERSIONED_WATCHERS: &str = r#"(
+        watchers: {
+            "dev": (
+                name: ("dev"),
+                path: "/dev",
+                created_at: "2025-01-01T00:00:00Z",
+                system_managed: false,
+            ),
+        },
+        last_updated: "2025-01-01T00:00:00Z",
+    )"#;
+
+    #[test]
+    fn test_unversioned_file_is_upgraded_and_backed_up() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = &temp_dir.path().join("watchers.ron");
+        let loaded: Loaded<WatcherRegistry> = WATCHER_REGISTRY.parse(path, UNVERSIONED_WATCHERS)?;
+
+        assert!(loaded.is_upgraded());
+        assert_eq!(loaded.value.schema_version, WATCHER_REGISTRY_VERSION);
+        assert_eq!(loaded.value.watchers["dev"].mode, WatchMode::Native);
+
+        loaded.back_up(&RealFileSystem, path)?;
+        assert_eq!(
+            RealFileSystem.read_to_string(&temp_dir.path().join("watchers.ron.v0.bak"))?,
+            UNVERSIONED_WATCHERS
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_newer_file_is_rejected() {
+        let content = "(schema_version: 99, watchers: {}, last_updated: \"2025-01-01T00:00:00Z\")";
+
+        assert!(matches!(
+            WATCHER_REGISTRY.parse::<WatcherRegistry>(Path::new("watchers.ron"), content),
+            Err(ProjectsError::SchemaTooNew { found: 99, .. })
+        ));
+    }
+}
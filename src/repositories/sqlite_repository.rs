@@ -0,0 +1,762 @@
+use crate::repositories::{HistoryRepository, ProjectRepository, WatcherRepository};
+use crate::types::*;
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension, Params, Transaction, TransactionBehavior, params};
+use std::path::{MAIN_SEPARATOR, Path};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS projects (
+        path TEXT PRIMARY KEY,
+        id INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS projects_by_id ON projects (id);
+    CREATE TABLE IF NOT EXISTS watchers (
+        name TEXT PRIMARY KEY,
+        path TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS size_history (
+        project_id INTEGER PRIMARY KEY,
+        data TEXT NOT NULL
+    );
+";
+
+/// Layout version of the tables above written by this binary
+const DATABASE_VERSION: u32 = 1;
+
+const SCHEMA_VERSION: &str = "schema_version";
+const NEXT_PROJECT_ID: &str = "next_project_id";
+const RON_IMPORTED_AT: &str = "ron_imported_at";
+
+/// `SQLite` database shared by the project and watcher repositories.
+///
+/// Rows keep the RON serialized project or watcher next to the columns they are looked up by,
+/// so lookups by id, path and watcher use an index instead of parsing the whole registry.
+pub struct SqliteDatabase {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteDatabase {
+    pub fn open(path: &Path) -> ProjectsResult<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let connection = Connection::open(path)?;
+        // The CLI and the daemon write to the same database
+        connection.busy_timeout(Duration::from_secs(5))?;
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        connection.execute_batch(SCHEMA)?;
+
+        match get_meta::<u32>(&connection, SCHEMA_VERSION)? {
+            Some(found) if found > DATABASE_VERSION => {
+                return Err(ProjectsError::SchemaTooNew {
+                    path: path.to_path_buf(),
+                    found,
+                    supported: DATABASE_VERSION,
+                });
+            }
+            Some(_) => {}
+            None => {
+                connection.execute(
+                    "INSERT INTO meta (key, value) VALUES (?1, ?2)",
+                    params![SCHEMA_VERSION, DATABASE_VERSION],
+                )?;
+            }
+        }
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// Whether the RON registries were already copied into this database.
+    pub fn is_imported(&self) -> ProjectsResult<bool> {
+        get_meta::<String>(&self.connection(), RON_IMPORTED_AT).map(|imported| imported.is_some())
+    }
+
+    /// Copies projects, watchers and size history loaded from the RON files, keeping project ids,
+    /// and records the import so it only happens once. `false` if another process imported first.
+    pub fn import(
+        &self,
+        projects: &[RustProject],
+        watchers: &[WatcherConfig],
+        history: &SizeHistory,
+    ) -> ProjectsResult<bool> {
+        self.write(|transaction| {
+            if get_meta::<String>(transaction, RON_IMPORTED_AT)?.is_some() {
+                return Ok(false);
+            }
+            for project in projects {
+                insert_project(transaction, project)?;
+            }
+            for watcher in watchers {
+                insert_watcher(transaction, watcher)?;
+            }
+            for (id, snapshots) in &history.projects {
+                insert_snapshots(transaction, *id, snapshots)?;
+            }
+
+            let next_id = projects
+                .iter()
+                .map(|project| project.id.next())
+                .max()
+                .unwrap_or(ProjectId::new(1))
+                .max(next_project_id(transaction)?);
+            set_meta(transaction, NEXT_PROJECT_ID, next_id.get())?;
+            set_meta(transaction, RON_IMPORTED_AT, Utc::now().to_rfc3339()).map(|()| true)
+        })
+    }
+
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        self.connection.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Runs `update` in a transaction that holds the database write lock from the start.
+    fn write<T>(&self, update: impl FnOnce(&Transaction) -> ProjectsResult<T>) -> ProjectsResult<T> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let result = update(&transaction)?;
+        transaction.commit()?;
+        drop(connection);
+        Ok(result)
+    }
+}
+
+pub struct SqliteProjectRepository {
+    database: Arc<SqliteDatabase>,
+}
+
+impl SqliteProjectRepository {
+    pub const fn new(database: Arc<SqliteDatabase>) -> Self {
+        Self { database }
+    }
+}
+
+impl ProjectRepository for SqliteProjectRepository {
+    fn find_all(&self) -> ProjectsResult<Vec<RustProject>> {
+        query_rows(&self.database.connection(), "SELECT data FROM projects", [])
+    }
+
+    fn find_by_id(&self, id: ProjectId) -> ProjectsResult<Option<RustProject>> {
+        query_rows(
+            &self.database.connection(),
+            "SELECT data FROM projects WHERE id = ?1",
+            [id.get()],
+        )
+        .map(|projects| projects.into_iter().next())
+    }
+
+    fn find_by_watcher(&self, watcher_name: &WatcherName) -> ProjectsResult<Vec<RustProject>> {
+        let connection = self.database.connection();
+        let watcher_path: Option<String> = connection
+            .query_row(
+                "SELECT path FROM watchers WHERE name = ?1",
+                [watcher_name.as_str()],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        let projects = watcher_path.map_or(Ok(Vec::new()), |path| {
+            projects_at_or_below(&connection, Path::new(&path))
+        });
+        drop(connection);
+        projects
+    }
+
+    fn find_by_path(&self, path: &Path) -> ProjectsResult<Option<RustProject>> {
+        let canonical_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        find_project_at(&self.database.connection(), &canonical_path)
+    }
+
+    fn find_containing_project(&self, path: &Path) -> ProjectsResult<Option<RustProject>> {
+        let canonical_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let connection = self.database.connection();
+
+        for ancestor in canonical_path.ancestors() {
+            if let Some(project) = find_project_at(&connection, ancestor)? {
+                return Ok(Some(project));
+            }
+        }
+        Ok(None)
+    }
+
+    fn save(&self, project: RustProject) -> ProjectsResult<ProjectId> {
+        self.database.write(|transaction| add_project(transaction, project))
+    }
+
+    fn save_all(&self, projects: Vec<RustProject>) -> ProjectsResult<Vec<ProjectId>> {
+        self.database.write(|transaction| {
+            projects
+                .into_iter()
+                .map(|project| add_project(transaction, project))
+                .collect()
+        })
+    }
+
+    fn remove(&self, id: ProjectId) -> ProjectsResult<bool> {
+        self.database.write(|transaction| {
+            transaction
+                .execute("DELETE FROM projects WHERE id = ?1", [id.get()])
+                .map(|removed| removed > 0)
+                .map_err(Into::into)
+        })
+    }
+
+    fn remove_all(&self, ids: &[ProjectId]) -> ProjectsResult<Vec<ProjectId>> {
+        self.database.write(|transaction| {
+            let mut removed = Vec::new();
+            for &id in ids {
+                if transaction.execute("DELETE FROM projects WHERE id = ?1", [id.get()])? > 0 {
+                    removed.push(id);
+                }
+            }
+            Ok(removed)
+        })
+    }
+
+    fn relocate(&self, from: &Path, to: &Path) -> ProjectsResult<usize> {
+        self.database.write(|transaction| {
+            let moved = projects_at_or_below(transaction, from)?;
+            for project in &moved {
+                let new_path = relocated_path(&project.path, from, to);
+                let is_moved = |path: &Path| moved.iter().any(|project| project.path == path);
+                if !is_moved(&new_path) && find_project_at(transaction, &new_path)?.is_some() {
+                    return Err(ProjectsError::RelocationTargetTracked { path: new_path });
+                }
+            }
+            for project in &moved {
+                transaction.execute("DELETE FROM projects WHERE path = ?1", [path_key(&project.path)])?;
+                insert_project(
+                    transaction,
+                    &project.clone().with_paths_mapped(|path| relocated_path(path, from, to)),
+                )?;
+            }
+            Ok(moved.len())
+        })
+    }
+
+    fn annotate(
+        &self,
+        id: ProjectId,
+        edit: &dyn Fn(&mut ProjectAnnotations),
+    ) -> ProjectsResult<Option<RustProject>> {
+        self.database.write(|transaction| {
+            let found: Option<RustProject> =
+                query_rows(transaction, "SELECT data FROM projects WHERE id = ?1", [id.get()])?
+                    .into_iter()
+                    .next();
+            let Some(mut project) = found else {
+                return Ok(None);
+            };
+            edit(&mut project.annotations);
+            insert_project(transaction, &project).map(|()| Some(project))
+        })
+    }
+
+    fn renumber(&self) -> ProjectsResult<Vec<(ProjectId, ProjectId)>> {
+        self.database.write(|transaction| {
+            let projects: Vec<RustProject> =
+                query_rows(transaction, "SELECT data FROM projects ORDER BY id", [])?;
+
+            let mut next_id = ProjectId::new(1);
+            let mut changed = Vec::new();
+            for project in projects {
+                if project.id != next_id {
+                    changed.push((project.id, next_id));
+                    insert_project(transaction, &RustProject { id: next_id, ..project })?;
+                }
+                next_id = next_id.next();
+            }
+
+            set_meta(transaction, NEXT_PROJECT_ID, next_id.get()).map(|()| changed)
+        })
+    }
+
+    fn import(&self, projects: Vec<RustProject>, mode: ImportMode) -> ProjectsResult<usize> {
+        self.database.write(|transaction| {
+            if mode == ImportMode::Replace {
+                transaction.execute("DELETE FROM projects", [])?;
+                set_meta(transaction, NEXT_PROJECT_ID, 1)?;
+            }
+
+            let mut renumbered = 0;
+            for project in projects {
+                if merge_project(transaction, project)? {
+                    renumbered += 1;
+                }
+            }
+            Ok(renumbered)
+        })
+    }
+
+    fn exists(&self, path: &Path) -> ProjectsResult<bool> {
+        self.find_by_path(path).map(|project| project.is_some())
+    }
+
+    fn remove_all_projects(&self) -> ProjectsResult<bool> {
+        self.database.write(|transaction| {
+            transaction.execute("DELETE FROM projects", [])?;
+            Ok(true)
+        })
+    }
+}
+
+pub struct SqliteWatcherRepository {
+    database: Arc<SqliteDatabase>,
+}
+
+impl SqliteWatcherRepository {
+    pub const fn new(database: Arc<SqliteDatabase>) -> Self {
+        Self { database }
+    }
+}
+
+impl WatcherRepository for SqliteWatcherRepository {
+    fn find_all(&self) -> ProjectsResult<Vec<WatcherConfig>> {
+        query_rows(&self.database.connection(), "SELECT data FROM watchers", [])
+    }
+
+    fn find_by_name(&self, name: &WatcherName) -> ProjectsResult<Option<WatcherConfig>> {
+        find_watcher(&self.database.connection(), name)
+    }
+
+    fn save(&self, watcher: WatcherConfig) -> ProjectsResult<()> {
+        self.database.write(|transaction| insert_watcher(transaction, &watcher))
+    }
+
+    fn save_health(&self, name: &WatcherName, health: WatcherHealth) -> ProjectsResult<bool> {
+        self.database.write(|transaction| {
+            find_watcher(transaction, name)?.map_or(Ok(false), |watcher| {
+                insert_watcher(transaction, &WatcherConfig { health, ..watcher }).map(|()| true)
+            })
+        })
+    }
+
+    fn remove(&self, name: &WatcherName) -> ProjectsResult<bool> {
+        self.database.write(|transaction| {
+            transaction
+                .execute("DELETE FROM watchers WHERE name = ?1", [name.as_str()])
+                .map(|removed| removed > 0)
+                .map_err(Into::into)
+        })
+    }
+
+    fn rename(&self, name: &WatcherName, watcher: WatcherConfig) -> ProjectsResult<bool> {
+        self.database.write(|transaction| {
+            transaction
+                .execute(
+                    "UPDATE watchers SET name = ?1, path = ?2, data = ?3 WHERE name = ?4",
+                    params![
+                        watcher.name.as_str(),
+                        path_key(&watcher.path),
+                        ron::to_string(&watcher)?,
+                        name.as_str()
+                    ],
+                )
+                .map(|renamed| renamed > 0)
+                .map_err(Into::into)
+        })
+    }
+
+    fn import(&self, watchers: Vec<WatcherConfig>, mode: ImportMode) -> ProjectsResult<()> {
+        self.database.write(|transaction| {
+            if mode == ImportMode::Replace {
+                transaction.execute("DELETE FROM watchers", [])?;
+            }
+            for watcher in &watchers {
+                insert_watcher(transaction, watcher)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn exists(&self, name: &WatcherName) -> ProjectsResult<bool> {
+        self.find_by_name(name).map(|watcher| watcher.is_some())
+    }
+
+    fn remove_all_watchers(&self) -> ProjectsResult<bool> {
+        self.database.write(|transaction| {
+            transaction.execute("DELETE FROM watchers", [])?;
+            Ok(true)
+        })
+    }
+}
+
+pub struct SqliteHistoryRepository {
+    database: Arc<SqliteDatabase>,
+}
+
+impl SqliteHistoryRepository {
+    pub const fn new(database: Arc<SqliteDatabase>) -> Self {
+        Self { database }
+    }
+}
+
+impl HistoryRepository for SqliteHistoryRepository {
+    fn find_by_project(&self, id: ProjectId) -> ProjectsResult<Vec<SizeSnapshot>> {
+        find_snapshots(&self.database.connection(), id)
+    }
+
+    fn record(&self, snapshots: Vec<(ProjectId, SizeSnapshot)>) -> ProjectsResult<()> {
+        self.database.write(|transaction| {
+            for (id, snapshot) in snapshots {
+                let mut history = find_snapshots(transaction, id)?;
+                snapshot.append_to(&mut history);
+                insert_snapshots(transaction, id, &history)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn remove(&self, ids: &[ProjectId]) -> ProjectsResult<()> {
+        self.database.write(|transaction| {
+            for id in ids {
+                transaction.execute("DELETE FROM size_history WHERE project_id = ?1", [id.get()])?;
+            }
+            Ok(())
+        })
+    }
+
+    fn renumber(&self, changes: &[(ProjectId, ProjectId)]) -> ProjectsResult<()> {
+        self.database.write(|transaction| {
+            let mut moved = Vec::new();
+            for (old, new) in changes {
+                moved.push((*new, find_snapshots(transaction, *old)?));
+                transaction.execute("DELETE FROM size_history WHERE project_id = ?1", [old.get()])?;
+            }
+            for (id, snapshots) in &moved {
+                insert_snapshots(transaction, *id, snapshots)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn remove_all(&self) -> ProjectsResult<()> {
+        self.database.write(|transaction| {
+            transaction.execute("DELETE FROM size_history", [])?;
+            Ok(())
+        })
+    }
+}
+
+fn path_key(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Deserializes the RON `data` column of every row `sql` selects.
+fn query_rows<T: serde::de::DeserializeOwned>(
+    connection: &Connection,
+    sql: &str,
+    params: impl Params,
+) -> ProjectsResult<Vec<T>> {
+    let mut statement = connection.prepare_cached(sql)?;
+    let rows = statement.query_map(params, |row| row.get::<_, String>(0))?;
+    rows.map(|data| Ok(ron::from_str(&data?)?)).collect()
+}
+
+fn find_project_at(connection: &Connection, path: &Path) -> ProjectsResult<Option<RustProject>> {
+    query_rows(connection, "SELECT data FROM projects WHERE path = ?1", [path_key(path)])
+        .map(|projects| projects.into_iter().next())
+}
+
+/// Projects at `root` or below it, as a range scan over the path index.
+fn projects_at_or_below(
+    connection: &Connection,
+    root: &Path,
+) -> ProjectsResult<Vec<RustProject>> {
+    let root = path_key(root);
+    let prefix = root.trim_end_matches(MAIN_SEPARATOR);
+    // Every path below `prefix` sorts between `prefix/` and the character after the separator
+    let after_separator = char::from(MAIN_SEPARATOR as u8 + 1);
+
+    query_rows(
+        connection,
+        "SELECT data FROM projects WHERE path = ?1 OR (path >= ?2 AND path < ?3)",
+        params![
+            root,
+            format!("{prefix}{MAIN_SEPARATOR}"),
+            format!("{prefix}{after_separator}")
+        ],
+    )
+}
+
+fn find_watcher(connection: &Connection, name: &WatcherName) -> ProjectsResult<Option<WatcherConfig>> {
+    query_rows(connection, "SELECT data FROM watchers WHERE name = ?1", [name.as_str()])
+        .map(|watchers| watchers.into_iter().next())
+}
+
+fn find_snapshots(connection: &Connection, id: ProjectId) -> ProjectsResult<Vec<SizeSnapshot>> {
+    query_rows(connection, "SELECT data FROM size_history WHERE project_id = ?1", [id.get()])
+        .map(|histories: Vec<Vec<SizeSnapshot>>| histories.into_iter().next().unwrap_or_default())
+}
+
+fn insert_snapshots(
+    transaction: &Transaction,
+    id: ProjectId,
+    snapshots: &[SizeSnapshot],
+) -> ProjectsResult<()> {
+    if snapshots.is_empty() {
+        return Ok(());
+    }
+    transaction.execute(
+        "INSERT OR REPLACE INTO size_history (project_id, data) VALUES (?1, ?2)",
+        params![id.get(), ron::to_string(snapshots)?],
+    )?;
+    Ok(())
+}
+
+fn get_meta<T: rusqlite::types::FromSql>(connection: &Connection, key: &str) -> ProjectsResult<Option<T>> {
+    connection
+        .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| row.get(0))
+        .optional()
+        .map_err(Into::into)
+}
+
+fn set_meta(transaction: &Transaction, key: &str, value: impl rusqlite::ToSql) -> ProjectsResult<()> {
+    transaction.execute(
+        "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+fn next_project_id(connection: &Connection) -> ProjectsResult<ProjectId> {
+    get_meta(connection, NEXT_PROJECT_ID).map(|id| ProjectId::new(id.unwrap_or(1)))
+}
+
+fn tracked_id(transaction: &Transaction, path: &Path) -> ProjectsResult<Option<ProjectId>> {
+    transaction
+        .query_row("SELECT id FROM projects WHERE path = ?1", [path_key(path)], |row| {
+            row.get(0).map(ProjectId::new)
+        })
+        .optional()
+        .map_err(Into::into)
+}
+
+/// Same as `ProjectRegistry::add_project`: a tracked path keeps its id and annotations, a new one
+/// gets the next id.
+fn add_project(transaction: &Transaction, project: RustProject) -> ProjectsResult<ProjectId> {
+    let project = if let Some(tracked) = find_project_at(transaction, &project.path)? {
+        RustProject {
+            id: tracked.id,
+            annotations: tracked.annotations,
+            ..project
+        }
+    } else {
+        let id = next_project_id(transaction)?;
+        set_meta(transaction, NEXT_PROJECT_ID, id.next().get())?;
+        RustProject { id, ..project }
+    };
+    insert_project(transaction, &project).map(|()| project.id)
+}
+
+/// Same as `ProjectRegistry::merge_project`.
+fn merge_project(transaction: &Transaction, mut project: RustProject) -> ProjectsResult<bool> {
+    let id_taken = transaction
+        .query_row("SELECT 1 FROM projects WHERE id = ?1", [project.id.get()], |_| Ok(()))
+        .optional()?
+        .is_some();
+
+    let next_id = next_project_id(transaction)?;
+    let mut renumbered = false;
+    if let Some(id) = tracked_id(transaction, &project.path)? {
+        project.id = id;
+    } else if id_taken {
+        project.id = next_id;
+        renumbered = true;
+    }
+
+    set_meta(transaction, NEXT_PROJECT_ID, next_id.max(project.id.next()).get())?;
+    insert_project(transaction, &project).map(|()| renumbered)
+}
+
+fn insert_project(transaction: &Transaction, project: &RustProject) -> ProjectsResult<()> {
+    transaction.execute(
+        "INSERT OR REPLACE INTO projects (path, id, data) VALUES (?1, ?2, ?3)",
+        params![path_key(&project.path), project.id.get(), ron::to_string(project)?],
+    )?;
+    Ok(())
+}
+
+fn insert_watcher(transaction: &Transaction, watcher: &WatcherConfig) -> ProjectsResult<()> {
+    transaction.execute(
+        "INSERT OR REPLACE INTO watchers (name, path, data) VALUES (?1, ?2, ?3)",
+        params![watcher.name.as_str(), path_key(&watcher.path), ron::to_string(watcher)?],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::path::PathBuf;
+
+    fn project(path: &str) -> RustProject {
+        RustProject {
+            id: ProjectId::new(0),
+            name: ProjectName::new(path.rsplit('/').next().unwrap_or(path).to_string()),
+            path: PathBuf::from(path),
+            version: ProjectVersion::new("0.1.0".to_string()),
+            created_at: Utc::now(),
+            last_modified: Utc::now(),
+            size_bytes: FileSize::new(0),
+            target_size_bytes: FileSize::new(0),
+            dependencies_count: DependencyCount::new(0),
+            estimated_build_time_seconds: TimingDuration::new(0),
+            project_type: ProjectType::Package,
+            annotations: ProjectAnnotations::default(),
+            members: Vec::new(),
+        }
+    }
+
+    fn watcher(name: &str, path: &str) -> WatcherConfig {
+        WatcherConfig {
+            name: WatcherName::new(name.to_string()),
+            path: PathBuf::from(path),
+            created_at: Utc::now(),
+            system_managed: false,
+            rules: WatchRules::default(),
+            mode: WatchMode::Native,
+            paused: false,
+            health: WatcherHealth::default(),
+        }
+    }
+
+    #[test]
+    fn test_lookups_by_id_path_and_watcher() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let database = Arc::new(SqliteDatabase::open(&temp_dir.path().join("registry.sqlite3"))?);
+        let projects = SqliteProjectRepository::new(database.clone());
+        let watchers = SqliteWatcherRepository::new(database);
+
+        watchers.save(watcher("games", "/dev/games"))?;
+        projects.save_all(vec![
+            project("/dev/games/snake"),
+            project("/dev/games-old/tetris"),
+            project("/dev/tools/grep"),
+        ])?;
+
+        let snake = projects.find_by_path(Path::new("/dev/games/snake"))?.unwrap();
+        assert_eq!(projects.find_by_id(snake.id)?.unwrap().path, snake.path);
+        assert_eq!(
+            projects
+                .find_containing_project(Path::new("/dev/games/snake/src/main.rs"))?
+                .map(|project| project.id),
+            Some(snake.id)
+        );
+
+        let in_games = projects.find_by_watcher(&WatcherName::new("games".to_string()))?;
+        assert_eq!(in_games.len(), 1);
+        assert_eq!(in_games[0].path, PathBuf::from("/dev/games/snake"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_relocate_refuses_to_replace_tracked_projects() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let projects = SqliteProjectRepository::new(Arc::new(SqliteDatabase::open(
+            &temp_dir.path().join("registry.sqlite3"),
+        )?));
+        projects.save_all(vec![project("/dev/games/snake"), project("/old/games/snake")])?;
+
+        assert!(matches!(
+            projects.relocate(Path::new("/dev/games"), Path::new("/old/games")),
+            Err(ProjectsError::RelocationTargetTracked { .. })
+        ));
+        assert!(projects.find_by_path(Path::new("/dev/games/snake"))?.is_some());
+        assert_eq!(projects.relocate(Path::new("/dev/games"), Path::new("/new/games"))?, 1);
+        assert_eq!(projects.find_all()?.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_keeps_ids_and_continues_numbering() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let database = Arc::new(SqliteDatabase::open(&temp_dir.path().join("registry.sqlite3"))?);
+        let imported = RustProject {
+            id: ProjectId::new(7),
+            ..project("/dev/old")
+        };
+
+        assert!(!database.is_imported()?);
+        assert!(database.import(
+            std::slice::from_ref(&imported),
+            &[watcher("dev", "/dev")],
+            &SizeHistory::new()
+        )?);
+        assert!(database.is_imported()?);
+        assert!(!database.import(&[imported], &[], &SizeHistory::new())?);
+
+        let projects = SqliteProjectRepository::new(database.clone());
+        projects.save(project("/dev/new"))?;
+        assert_eq!(projects.find_by_path(Path::new("/dev/old"))?.unwrap().id, ProjectId::new(7));
+        assert_eq!(projects.find_by_path(Path::new("/dev/new"))?.unwrap().id, ProjectId::new(8));
+        assert_eq!(SqliteWatcherRepository::new(database).find_all()?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_saving_a_tracked_path_keeps_its_id() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let database = Arc::new(SqliteDatabase::open(&temp_dir.path().join("registry.sqlite3"))?);
+        let projects = SqliteProjectRepository::new(database);
+
+        projects.save_all(vec![project("/dev/a"), project("/dev/b"), project("/dev/c")])?;
+        projects.save(project("/dev/b"))?;
+        assert_eq!(projects.find_by_path(Path::new("/dev/b"))?.unwrap().id, ProjectId::new(2));
+
+        projects.remove(ProjectId::new(1))?;
+        assert_eq!(projects.renumber()?.len(), 2);
+        assert_eq!(projects.find_by_id(ProjectId::new(2))?.unwrap().path, PathBuf::from("/dev/c"));
+        projects.save(project("/dev/d"))?;
+        assert_eq!(projects.find_by_path(Path::new("/dev/d"))?.unwrap().id, ProjectId::new(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_annotations_survive_rescans() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let database = Arc::new(SqliteDatabase::open(&temp_dir.path().join("registry.sqlite3"))?);
+        let projects = SqliteProjectRepository::new(database);
+        let id = projects.save(project("/dev/a"))?;
+
+        let note = |annotations: &mut ProjectAnnotations| annotations.note = Some("keep".to_string());
+        assert!(projects.annotate(id, &note)?.is_some());
+        assert!(projects.annotate(ProjectId::new(9), &note)?.is_none());
+
+        projects.save(project("/dev/a"))?;
+        assert_eq!(projects.find_by_id(id)?.unwrap().annotations.note.as_deref(), Some("keep"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_history_follows_renumbered_ids() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let database = Arc::new(SqliteDatabase::open(&temp_dir.path().join("registry.sqlite3"))?);
+        let history = SqliteHistoryRepository::new(database);
+        let snapshot = |bytes| SizeSnapshot {
+            recorded_at: Utc::now(),
+            size_bytes: FileSize::new(bytes),
+            target_size_bytes: FileSize::new(bytes),
+        };
+
+        history.record(vec![(ProjectId::new(2), snapshot(100)), (ProjectId::new(3), snapshot(300))])?;
+        history.record(vec![(ProjectId::new(2), snapshot(200))])?;
+        assert_eq!(history.find_by_project(ProjectId::new(2))?.len(), 1);
+        assert_eq!(history.find_by_project(ProjectId::new(2))?[0].size_bytes, FileSize::new(200));
+
+        history.remove(&[ProjectId::new(2)])?;
+        history.renumber(&[(ProjectId::new(3), ProjectId::new(1))])?;
+        assert_eq!(history.find_by_project(ProjectId::new(3))?, []);
+        assert_eq!(history.find_by_project(ProjectId::new(1))?[0].size_bytes, FileSize::new(300));
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
 use crate::infrastructure::FileSystem;
+use crate::repositories::BackupStore;
+use crate::repositories::schema::{self, Loaded};
 use crate::types::*;
 use ron::ser::{PrettyConfig, to_string_pretty};
 use std::sync::Arc;
@@ -8,7 +10,14 @@ pub trait WatcherRepository: Send + Sync {
     #[allow(dead_code)]
     fn find_by_name(&self, name: &WatcherName) -> ProjectsResult<Option<WatcherConfig>>;
     fn save(&self, watcher: WatcherConfig) -> ProjectsResult<()>;
+    /// Replaces only the health of a stored watcher, `false` if there is no such watcher.
+    fn save_health(&self, name: &WatcherName, health: WatcherHealth) -> ProjectsResult<bool>;
     fn remove(&self, name: &WatcherName) -> ProjectsResult<bool>;
+    /// Stores `watcher` in place of the watcher called `name` in one write, `false` if there is no
+    /// such watcher.
+    fn rename(&self, name: &WatcherName, watcher: WatcherConfig) -> ProjectsResult<bool>;
+    /// Adds watchers from another registry, replacing tracked watchers with the same name.
+    fn import(&self, watchers: Vec<WatcherConfig>, mode: ImportMode) -> ProjectsResult<()>;
     #[allow(dead_code)]
     fn exists(&self, name: &WatcherName) -> ProjectsResult<bool>;
     fn remove_all_watchers(&self) -> ProjectsResult<bool>;
@@ -17,6 +26,7 @@ pub trait WatcherRepository: Send + Sync {
 pub struct FileWatcherRepository<F: FileSystem> {
     file_system: Arc<F>,
     registry_path: std::path::PathBuf,
+    backups: Option<Arc<BackupStore<F>>>,
 }
 
 impl<F: FileSystem> FileWatcherRepository<F> {
@@ -24,21 +34,50 @@ impl<F: FileSystem> FileWatcherRepository<F> {
         Self {
             file_system,
             registry_path,
+            backups: None,
         }
     }
 
+    /// Backs the registry up before it is overwritten.
+    #[must_use]
+    pub fn with_backups(mut self, backups: Arc<BackupStore<F>>) -> Self {
+        self.backups = Some(backups);
+        self
+    }
+
     fn load_registry(&self) -> ProjectsResult<WatcherRegistry> {
+        self.read_registry().map(|loaded| loaded.value)
+    }
+
+    fn read_registry(&self) -> ProjectsResult<Loaded<WatcherRegistry>> {
         match self.file_system.exists(&self.registry_path) {
             true => self
                 .file_system
                 .read_to_string(&self.registry_path)
-                .map_err(Into::into)
-                .and_then(|content| ron::from_str(&content).map_err(Into::into)),
-            false => Ok(WatcherRegistry::new()),
+                .and_then(|content| schema::WATCHER_REGISTRY.parse(&self.registry_path, &content)),
+            false => Ok(Loaded::current(WatcherRegistry::new())),
+        }
+    }
+
+    /// Runs a load/modify/save cycle under the registry lock so concurrent writers cannot lose
+    /// each other's updates.
+    fn with_locked_registry<T>(
+        &self,
+        update: impl FnOnce(WatcherRegistry) -> ProjectsResult<T>,
+    ) -> ProjectsResult<T> {
+        let _lock = self.file_system.lock(&self.registry_path)?;
+        let loaded = self.read_registry()?;
+        if loaded.is_upgraded() {
+            loaded.back_up(self.file_system.as_ref(), &self.registry_path)?;
+            self.save_registry(&loaded.value)?;
         }
+        update(loaded.value)
     }
 
     fn save_registry(&self, registry: &WatcherRegistry) -> ProjectsResult<()> {
+        if let Some(backups) = &self.backups {
+            backups.back_up(&self.registry_path)?;
+        }
         to_string_pretty(registry, PrettyConfig::default())
             .map_err(Into::into)
             .and_then(|content| self.file_system.write(&self.registry_path, &content))
@@ -57,14 +96,24 @@ impl<F: FileSystem> WatcherRepository for FileWatcherRepository<F> {
     }
 
     fn save(&self, watcher: WatcherConfig) -> ProjectsResult<()> {
-        self.load_registry().and_then(|mut r| {
+        self.with_locked_registry(|mut r| {
             r.watchers.insert(watcher.name.to_string(), watcher);
             self.save_registry(&r)
         })
     }
 
+    fn save_health(&self, name: &WatcherName, health: WatcherHealth) -> ProjectsResult<bool> {
+        self.with_locked_registry(|mut registry| {
+            registry
+                .watchers
+                .get_mut(name.as_str())
+                .map(|watcher| watcher.health = health)
+                .map_or(Ok(false), |()| self.save_registry(&registry).map(|()| true))
+        })
+    }
+
     fn remove(&self, name: &WatcherName) -> ProjectsResult<bool> {
-        self.load_registry().and_then(|mut registry| {
+        self.with_locked_registry(|mut registry| {
             registry
                 .watchers
                 .remove(name.as_str())
@@ -72,13 +121,35 @@ impl<F: FileSystem> WatcherRepository for FileWatcherRepository<F> {
         })
     }
 
+    fn rename(&self, name: &WatcherName, watcher: WatcherConfig) -> ProjectsResult<bool> {
+        self.with_locked_registry(|mut registry| {
+            if registry.watchers.remove(name.as_str()).is_none() {
+                return Ok(false);
+            }
+            registry.watchers.insert(watcher.name.to_string(), watcher);
+            self.save_registry(&registry).map(|()| true)
+        })
+    }
+
+    fn import(&self, watchers: Vec<WatcherConfig>, mode: ImportMode) -> ProjectsResult<()> {
+        self.with_locked_registry(|mut registry| {
+            if mode == ImportMode::Replace {
+                registry.watchers.clear();
+            }
+            for watcher in watchers {
+                registry.watchers.insert(watcher.name.to_string(), watcher);
+            }
+            self.save_registry(&registry)
+        })
+    }
+
     fn exists(&self, name: &WatcherName) -> ProjectsResult<bool> {
         self.load_registry()
             .map(|registry| registry.watchers.contains_key(name.as_str()))
     }
 
     fn remove_all_watchers(&self) -> ProjectsResult<bool> {
-        self.load_registry().and_then(|mut registry| {
+        self.with_locked_registry(|mut registry| {
             registry.watchers.clear();
             self.save_registry(&registry).map(|_| true)
         })
@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+use crate::types::*;
+use crate::infrastructure::FileSystem;
+use crate::repositories::{Backup, BackupStore};
+
+pub struct BackupService<F: FileSystem> {
+    store: BackupStore<F>,
+    /// Files covered by the backups
+    files: Vec<PathBuf>,
+}
+
+impl<F: FileSystem> BackupService<F> {
+    pub const fn new(store: BackupStore<F>, files: Vec<PathBuf>) -> Self {
+        Self { store, files }
+    }
+
+    pub fn list_backups(&self) -> ProjectsResult<Vec<Backup>> {
+        self.store.list()
+    }
+
+    /// Rolls the registries back to how they were at `timestamp`, returning the restored files.
+    /// Fails when the storage backend reads none of the backed up files.
+    pub fn restore(&self, timestamp: BackupTimestamp) -> ProjectsResult<Vec<PathBuf>> {
+        if self.files.is_empty() {
+            return Err(ProjectsError::BackupsNotUsed);
+        }
+        self.store.restore(timestamp, &self.files)
+    }
+}
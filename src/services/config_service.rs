@@ -3,30 +3,138 @@ use std::sync::Arc;
 use serde::{Serialize, Deserialize};
 use crate::types::*;
 use crate::repositories::ConfigRepository;
+use crate::services::default_config_dir;
+
+/// Layout version of `config.ron` written by this binary
+pub const CONFIG_VERSION: u32 = 1;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
+    /// Files written before versioning was introduced have no version and count as 0
+    #[serde(default)]
+    pub schema_version: u32,
     pub registry_path: PathBuf,
     pub watcher_registry_path: PathBuf,
+    /// Scan depth for watchers that do not set `max_depth`, unlimited when `None`
     pub max_scan_depth: Option<usize>,
+    /// Threads per directory walk, chosen from the CPU count when `None`
     pub thread_count: Option<usize>,
+    /// Keep estimated build times in `target/.build-time-cache` instead of re-running `cargo check`
     pub cache_build_times: bool,
+    /// Never search `target` directories for projects
     pub ignore_target_dirs: bool,
+    /// Directory names never searched for projects, `target` is governed by `ignore_target_dirs`
+    #[serde(default = "default_ignored_dirs")]
+    pub ignored_dirs: Vec<String>,
+    /// Roots searched by `watch --system-wide`, the home directory when empty
+    #[serde(default)]
+    pub system_wide_roots: Vec<PathBuf>,
+    /// How often the daemon re-walks projects to correct its incrementally tracked sizes
+    #[serde(default = "default_size_reconcile_interval_secs")]
+    pub size_reconcile_interval_secs: u64,
+    /// Poll interval for watchers that fall back to polling when the inotify watch limit is reached
+    #[serde(default = "default_fallback_poll_interval_secs")]
+    pub fallback_poll_interval_secs: u64,
+    /// Where projects and watchers are stored
+    #[serde(default)]
+    pub storage: StorageBackend,
+    /// Size snapshots of every project, used by the `Ron` storage backend
+    #[serde(default = "default_history_path")]
+    pub history_path: PathBuf,
+    /// Where `registry.ron` and `watchers.ron` are backed up before being overwritten
+    #[serde(default = "default_backup_dir")]
+    pub backup_dir: PathBuf,
+    /// Backups kept per file, 0 turns backups off
+    #[serde(default = "default_backup_count")]
+    pub backup_count: usize,
+    /// Minimum time between two backups of the same file
+    #[serde(default = "default_backup_interval_secs")]
+    pub backup_interval_secs: u64,
+    /// Database file used by the `Sqlite` storage backend
+    #[serde(default = "default_database_path")]
+    pub database_path: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StorageBackend {
+    /// `registry_path` and `watcher_registry_path` RON files
+    #[default]
+    Ron,
+    /// A `SQLite` database at `database_path`, filled from the RON files the first time it is used
+    Sqlite,
+}
+
+const fn default_size_reconcile_interval_secs() -> u64 {
+    30 * 60
+}
+
+const fn default_fallback_poll_interval_secs() -> u64 {
+    30
+}
+
+fn default_ignored_dirs() -> Vec<String> {
+    [
+        "node_modules",
+        ".git",
+        ".svn",
+        "__pycache__",
+        ".vscode",
+        ".idea",
+        ".venv",
+        "build",
+        "dist",
+        "out",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+fn fallback_config_dir() -> PathBuf {
+    default_config_dir().unwrap_or_else(|_| PathBuf::from(".").join("cargo-projects"))
+}
+
+fn default_history_path() -> PathBuf {
+    fallback_config_dir().join("history.ron")
+}
+
+fn default_backup_dir() -> PathBuf {
+    fallback_config_dir().join("backups")
+}
+
+const fn default_backup_count() -> usize {
+    10
+}
+
+const fn default_backup_interval_secs() -> u64 {
+    60 * 60
+}
+
+fn default_database_path() -> PathBuf {
+    fallback_config_dir().join("registry.sqlite3")
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
-        let mut config_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."));
-        config_dir.push("cargo-projects");
+        let config_dir = fallback_config_dir();
 
         Self {
+            schema_version: CONFIG_VERSION,
             registry_path: config_dir.join("registry.ron"),
             watcher_registry_path: config_dir.join("watchers.ron"),
             max_scan_depth: Some(10),
             thread_count: None,
             cache_build_times: true,
             ignore_target_dirs: true,
+            ignored_dirs: default_ignored_dirs(),
+            system_wide_roots: Vec::new(),
+            size_reconcile_interval_secs: default_size_reconcile_interval_secs(),
+            fallback_poll_interval_secs: default_fallback_poll_interval_secs(),
+            storage: StorageBackend::default(),
+            history_path: default_history_path(),
+            backup_dir: default_backup_dir(),
+            backup_count: default_backup_count(),
+            backup_interval_secs: default_backup_interval_secs(),
+            database_path: default_database_path(),
         }
     }
 }
@@ -1,9 +1,11 @@
 pub mod project_service;
 pub mod config_service;
 pub mod watcher_service;
+pub mod backup_service;
 pub mod service_factory;
 
-pub use service_factory::{create_default_project_service, create_default_watcher_service, create_default_config_service};
-pub use config_service::{ConfigService, AppConfig};
+pub use service_factory::{create_default_project_service, create_cached_project_service, create_default_watcher_service, create_default_config_service, create_default_backup_service, default_config_dir, active_profile, set_config_location, ConfigLocation, HOME_ENV};
+pub use config_service::{ConfigService, AppConfig, StorageBackend, CONFIG_VERSION};
 pub use project_service::ProjectService;
-pub use watcher_service::WatcherService;
\ No newline at end of file
+pub use watcher_service::WatcherService;
+pub use backup_service::BackupService;
\ No newline at end of file
@@ -1,21 +1,25 @@
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use crate::types::*;
-use crate::repositories::{ProjectRepository, WatcherRepository};
+use crate::repositories::{HistoryRepository, ProjectRepository, WatcherRepository};
 
-pub struct ProjectService<P: ProjectRepository, W: WatcherRepository> {
+pub struct ProjectService<P: ProjectRepository + ?Sized, W: WatcherRepository + ?Sized> {
     project_repo: Arc<P>,
     #[allow(dead_code)]
     watcher_repo: Arc<W>,
+    history: Arc<dyn HistoryRepository>,
 }
 
-impl<P: ProjectRepository, W: WatcherRepository> ProjectService<P, W> {
+impl<P: ProjectRepository + ?Sized, W: WatcherRepository + ?Sized> ProjectService<P, W> {
     pub fn new(
         project_repo: Arc<P>,
         watcher_repo: Arc<W>,
+        history: Arc<dyn HistoryRepository>,
     ) -> Self {
         Self {
             project_repo,
             watcher_repo,
+            history,
         }
     }
 
@@ -27,6 +31,51 @@ impl<P: ProjectRepository, W: WatcherRepository> ProjectService<P, W> {
         self.project_repo.find_by_id(id)
     }
 
+    /// Finds the project a selector from the command line refers to. A name matches exactly
+    /// first, then as a prefix, and must single out one project either way.
+    pub fn resolve_project(&self, selector: &ProjectRef) -> ProjectsResult<RustProject> {
+        match selector {
+            ProjectRef::Id(id) => self
+                .get_project_by_id(*id)?
+                .ok_or(ProjectsError::ProjectNotFound { id: *id }),
+            ProjectRef::Path(path) => self.find_tracked_path(path),
+            ProjectRef::Name(name) => {
+                let projects = self.get_all_projects()?;
+                let exact: Vec<&RustProject> = projects.iter().filter(|p| p.name.is(name)).collect();
+                let matches = if exact.is_empty() {
+                    projects.iter().filter(|p| p.name.starts_with(name)).collect()
+                } else {
+                    exact
+                };
+
+                match matches.as_slice() {
+                    [project] => Ok((*project).clone()),
+                    // A bare directory name like `game` is still a path.
+                    [] if Path::new(name).is_dir() => self.find_tracked_path(Path::new(name)),
+                    [] => Err(ProjectsError::ProjectNameNotFound { name: name.clone() }),
+                    _ => {
+                        let mut matches = matches;
+                        matches.sort_by_key(|p| p.id);
+                        Err(ProjectsError::AmbiguousProject {
+                            query: name.clone(),
+                            candidates: matches
+                                .iter()
+                                .map(|p| format!("{} {} ({})", p.id, p.name, p.path.display()))
+                                .collect(),
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    fn find_tracked_path(&self, path: &Path) -> ProjectsResult<RustProject> {
+        // A deleted directory cannot be canonicalized, so fall back to the absolute path.
+        let path = path.canonicalize().or_else(|_| std::path::absolute(path))?;
+        self.find_project_by_path(&path)?
+            .ok_or(ProjectsError::ProjectPathNotTracked { path })
+    }
+
     pub fn get_projects_by_watcher(&self, watcher_name: &WatcherName) -> ProjectsResult<Vec<RustProject>> {
         self.project_repo.find_by_watcher(watcher_name)
     }
@@ -39,17 +88,131 @@ impl<P: ProjectRepository, W: WatcherRepository> ProjectService<P, W> {
         self.project_repo.find_containing_project(path)
     }
 
+    /// Saves the project and records its current sizes in its history.
+    #[allow(dead_code)]
     pub fn add_project(&self, project: RustProject) -> ProjectsResult<()> {
+        let snapshot = SizeSnapshot::of(&project);
+        let id = self.project_repo.save(project)?;
+        self.history.record(vec![(id, snapshot)])
+    }
+
+    /// Saves the project without recording its sizes, for callers that collect snapshots and
+    /// hand them to `record_sizes` in batches.
+    pub fn save_project(&self, project: RustProject) -> ProjectsResult<ProjectId> {
         self.project_repo.save(project)
     }
 
+    pub fn record_sizes(&self, snapshots: Vec<(ProjectId, SizeSnapshot)>) -> ProjectsResult<()> {
+        if snapshots.is_empty() {
+            return Ok(());
+        }
+        self.history.record(snapshots)
+    }
+
     pub fn add_projects(&self, projects: Vec<RustProject>) -> ProjectsResult<()> {
-        self.project_repo.save_all(projects)
+        if projects.is_empty() {
+            return Ok(());
+        }
+        let snapshots: Vec<SizeSnapshot> = projects.iter().map(SizeSnapshot::of).collect();
+        let ids = self.project_repo.save_all(projects)?;
+        self.history.record(ids.into_iter().zip(snapshots).collect())
+    }
+
+    /// Saves the projects like `add_projects` but keeps workspace members out of the registry.
+    /// Members of a saved or tracked workspace are dropped, and tracked projects that are members
+    /// of a saved workspace are folded into it, handing their tags, note and pin over.
+    /// Returns the projects that got a row of their own.
+    pub fn add_projects_with_members(&self, mut projects: Vec<RustProject>) -> ProjectsResult<Vec<RustProject>> {
+        let tracked = self.project_repo.find_all()?;
+        let member_paths: Vec<PathBuf> = projects
+            .iter()
+            .chain(&tracked)
+            .flat_map(|project| project.members.iter().map(|member| member.path.clone()))
+            .collect();
+        projects.retain(|project| !member_paths.contains(&project.path));
+
+        self.add_projects(projects.clone())?;
+
+        let mut folded_ids = Vec::new();
+        for workspace in projects.iter().filter(|project| !project.members.is_empty()) {
+            let folded: Vec<&RustProject> = tracked
+                .iter()
+                .filter(|project| workspace.has_member(&project.path))
+                .collect();
+            if folded.is_empty() {
+                continue;
+            }
+            let Some(id) = self.project_repo.find_by_path(&workspace.path)?.map(|saved| saved.id) else {
+                continue;
+            };
+
+            self.annotate_project(id, &|annotations| {
+                for member in &folded {
+                    annotations.absorb(member.annotations.clone());
+                }
+            })?;
+            folded_ids.extend(folded.iter().map(|member| member.id));
+        }
+        if !folded_ids.is_empty() {
+            self.remove_projects(&folded_ids)?;
+        }
+        Ok(projects)
+    }
+
+    pub fn get_size_history(&self, id: ProjectId) -> ProjectsResult<Vec<SizeSnapshot>> {
+        self.history.find_by_project(id)
     }
 
-    #[allow(dead_code)]
     pub fn remove_project(&self, id: ProjectId) -> ProjectsResult<bool> {
-        self.project_repo.remove(id)
+        let removed = self.project_repo.remove(id)?;
+        if removed {
+            self.history.remove(&[id])?;
+        }
+        Ok(removed)
+    }
+
+    /// Removes the projects and their history, returning how many were tracked.
+    pub fn remove_projects(&self, ids: &[ProjectId]) -> ProjectsResult<usize> {
+        let removed = self.project_repo.remove_all(ids)?;
+        if !removed.is_empty() {
+            self.history.remove(&removed)?;
+        }
+        Ok(removed.len())
+    }
+
+    /// Lets `edit` change the tags and note of a project, `None` when no project has the id.
+    pub fn annotate_project(
+        &self,
+        id: ProjectId,
+        edit: &dyn Fn(&mut ProjectAnnotations),
+    ) -> ProjectsResult<Option<RustProject>> {
+        self.project_repo.annotate(id, edit)
+    }
+
+    pub fn relocate_projects(&self, from: &std::path::Path, to: &std::path::Path) -> ProjectsResult<usize> {
+        self.project_repo.relocate(from, to)
+    }
+
+    /// Returns how many projects got a new id, their history moves along with them.
+    pub fn renumber_projects(&self) -> ProjectsResult<usize> {
+        let changes = self.project_repo.renumber()?;
+        self.history.renumber(&changes)?;
+        Ok(changes.len())
+    }
+
+    /// Adds projects from an export, returning how many could not keep their exported id.
+    /// Replacing the registry also drops the size history of the replaced projects.
+    pub fn import_projects(&self, projects: Vec<RustProject>, mode: ImportMode) -> ProjectsResult<usize> {
+        let renumbered = self.project_repo.import(projects, mode)?;
+        if mode == ImportMode::Replace {
+            self.history.remove_all()?;
+        }
+        Ok(renumbered)
+    }
+
+    /// Writes project updates the repository held back, see `create_cached_project_service`.
+    pub fn flush(&self) -> ProjectsResult<()> {
+        self.project_repo.flush()
     }
 
     pub fn project_exists(&self, path: &std::path::Path) -> ProjectsResult<bool> {
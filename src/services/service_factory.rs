@@ -1,17 +1,90 @@
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
 use crate::types::*;
-use crate::services::{ProjectService, WatcherService, ConfigService};
+use crate::infrastructure::RealFileSystem;
+use crate::repositories::{
+    BackupSettings, BackupStore, CachedProjectRepository, FileHistoryRepository, FileProjectRepository, FileWatcherRepository, HistoryRepository,
+    ProjectRepository, SqliteDatabase, SqliteHistoryRepository, SqliteProjectRepository,
+    SqliteWatcherRepository, WatcherRepository,
+};
+use crate::services::{
+    AppConfig, BackupService, ConfigService, ProjectService, StorageBackend, WatcherService,
+};
+use log::info;
+
+/// Environment variable pointing at the config directory, overridden by `--config-dir`
+pub const HOME_ENV: &str = "CARGO_PROJECTS_HOME";
+/// Environment variable naming the profile, overridden by `--profile`
+pub const PROFILE_ENV: &str = "CARGO_PROJECTS_PROFILE";
+
+/// Config directory and profile given on the command line
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLocation {
+    pub dir: Option<PathBuf>,
+    pub profile: Option<String>,
+}
+
+static CONFIG_LOCATION: OnceLock<ConfigLocation> = OnceLock::new();
+
+/// Makes every factory use `location` over the environment for the rest of the process. Only the
+/// first call has an effect.
+pub fn set_config_location(location: ConfigLocation) {
+    CONFIG_LOCATION.set(location).ok();
+}
+
+/// Directory holding `config.ron` and everything else the tool persists.
+///
+/// That is `--config-dir`, else `$CARGO_PROJECTS_HOME`, else `cargo-projects` in the user's config
+/// directory. A profile keeps its own files in `profiles/<name>` below it.
+pub fn default_config_dir() -> ProjectsResult<PathBuf> {
+    let location = CONFIG_LOCATION.get();
+    let dir = match location
+        .and_then(|location| location.dir.clone())
+        .or_else(|| env_value(HOME_ENV).map(PathBuf::from))
+    {
+        Some(dir) => std::path::absolute(dir)?,
+        None => dirs::config_dir()
+            .ok_or(ProjectsError::ConfigDirectoryNotFound)?
+            .join("cargo-projects"),
+    };
+
+    Ok(match active_profile()? {
+        Some(profile) => dir.join("profiles").join(profile),
+        None => dir,
+    })
+}
+
+/// `--profile`, else `$CARGO_PROJECTS_PROFILE`, `None` for the default profile.
+pub fn active_profile() -> ProjectsResult<Option<String>> {
+    let profile = CONFIG_LOCATION
+        .get()
+        .and_then(|location| location.profile.clone())
+        .or_else(|| env_value(PROFILE_ENV));
+
+    match profile {
+        Some(name)
+            if name.starts_with('.')
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) =>
+        {
+            Err(ProjectsError::InvalidProfileName { name })
+        }
+        profile => Ok(profile),
+    }
+}
+
+fn env_value(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|value| !value.is_empty())
+}
 
 /// Factory function for creating a config service with default implementation
 pub fn create_default_config_service() -> ProjectsResult<ConfigService<
     crate::repositories::FileConfigRepository<crate::infrastructure::RealFileSystem>,
 >> {
-    use crate::infrastructure::RealFileSystem;
     use crate::repositories::FileConfigRepository;
-    
-    let config_dir = dirs::config_dir()
-        .ok_or(ProjectsError::ConfigDirectoryNotFound)?
-        .join("cargo-projects");
+
+    let config_dir = default_config_dir()?;
 
     if !config_dir.exists() {
         std::fs::create_dir_all(&config_dir)?;
@@ -28,48 +101,135 @@ pub fn create_default_config_service() -> ProjectsResult<ConfigService<
     Ok(ConfigService::new(config_repo))
 }
 
-/// Factory function for creating a service with default implementations
-pub fn create_default_project_service() -> ProjectsResult<ProjectService<
-    crate::repositories::FileProjectRepository<crate::infrastructure::RealFileSystem>,
-    crate::repositories::FileWatcherRepository<crate::infrastructure::RealFileSystem>,
->> {
-    use crate::infrastructure::RealFileSystem;
-    use crate::repositories::{FileProjectRepository, FileWatcherRepository};
+/// Factory function for creating a service with the repositories of the configured storage backend
+pub fn create_default_project_service()
+-> ProjectsResult<ProjectService<dyn ProjectRepository, dyn WatcherRepository>> {
+    let config = create_default_config_service()?.get_config()?;
+    let repositories = create_repositories(&config)?;
 
-    let config_service = create_default_config_service()?;
-    let (registry_path, watcher_registry_path) = config_service.get_registry_paths()?;
-    
-    let file_system = Arc::new(RealFileSystem);
+    Ok(ProjectService::new(
+        repositories.projects,
+        repositories.watchers,
+        repositories.history,
+    ))
+}
 
-    let project_repo = Arc::new(FileProjectRepository::new(
-        file_system.clone(),
-        registry_path,
-        watcher_registry_path.clone(),
-    ));
-    
-    let watcher_repo = Arc::new(FileWatcherRepository::new(
-        file_system,
-        watcher_registry_path,
-    ));
+/// Like `create_default_project_service`, but reads the projects once and holds updates of tracked
+/// projects back until `ProjectService::flush`, for commands that look up or update many projects
+pub fn create_cached_project_service()
+-> ProjectsResult<ProjectService<dyn ProjectRepository, dyn WatcherRepository>> {
+    let config = create_default_config_service()?.get_config()?;
+    let repositories = create_repositories(&config)?;
 
-    Ok(ProjectService::new(project_repo, watcher_repo))
+    Ok(ProjectService::new(
+        Arc::new(CachedProjectRepository::new(repositories.projects)),
+        repositories.watchers,
+        repositories.history,
+    ))
 }
 
-/// Factory function for creating a watcher service with default implementations
-pub fn create_default_watcher_service() -> ProjectsResult<WatcherService<
-    crate::repositories::FileWatcherRepository<crate::infrastructure::RealFileSystem>,
->> {
-    use crate::infrastructure::RealFileSystem;
-    use crate::repositories::FileWatcherRepository;
+/// Factory function for creating a watcher service with the configured storage backend
+pub fn create_default_watcher_service() -> ProjectsResult<WatcherService<dyn WatcherRepository>> {
+    let config = create_default_config_service()?.get_config()?;
+    let repositories = create_repositories(&config)?;
+
+    Ok(WatcherService::new(repositories.watchers))
+}
+
+/// Factory function for the backups of the RON registries, which only the RON backend restores
+pub fn create_default_backup_service() -> ProjectsResult<BackupService<RealFileSystem>> {
+    let config = create_default_config_service()?.get_config()?;
+    let files = match config.storage {
+        StorageBackend::Ron => vec![config.registry_path.clone(), config.watcher_registry_path.clone()],
+        StorageBackend::Sqlite => Vec::new(),
+    };
+
+    Ok(BackupService::new(create_backup_store(&config), files))
+}
+
+fn create_backup_store(config: &AppConfig) -> BackupStore<RealFileSystem> {
+    BackupStore::new(
+        Arc::new(RealFileSystem),
+        BackupSettings {
+            dir: config.backup_dir.clone(),
+            keep: config.backup_count,
+            interval: chrono::TimeDelta::seconds(
+                i64::try_from(config.backup_interval_secs).unwrap_or(i64::MAX),
+            ),
+        },
+    )
+}
+
+struct Repositories {
+    projects: Arc<dyn ProjectRepository>,
+    watchers: Arc<dyn WatcherRepository>,
+    history: Arc<dyn HistoryRepository>,
+}
+
+fn create_repositories(config: &AppConfig) -> ProjectsResult<Repositories> {
+    match config.storage {
+        StorageBackend::Ron => {
+            let file_system = Arc::new(RealFileSystem);
+            let backups = Arc::new(create_backup_store(config));
+            Ok(Repositories {
+                projects: Arc::new(
+                    FileProjectRepository::new(
+                        file_system.clone(),
+                        config.registry_path.clone(),
+                        config.watcher_registry_path.clone(),
+                    )
+                    .with_backups(backups.clone()),
+                ),
+                watchers: Arc::new(
+                    FileWatcherRepository::new(
+                        file_system.clone(),
+                        config.watcher_registry_path.clone(),
+                    )
+                    .with_backups(backups),
+                ),
+                history: Arc::new(FileHistoryRepository::new(
+                    file_system,
+                    config.history_path.clone(),
+                )),
+            })
+        }
+        StorageBackend::Sqlite => {
+            let database = Arc::new(open_sqlite_database(config)?);
+            Ok(Repositories {
+                projects: Arc::new(SqliteProjectRepository::new(database.clone())),
+                watchers: Arc::new(SqliteWatcherRepository::new(database.clone())),
+                history: Arc::new(SqliteHistoryRepository::new(database)),
+            })
+        }
+    }
+}
+
+/// Opens the configured database, copying the RON registries into it the first time.
+fn open_sqlite_database(config: &AppConfig) -> ProjectsResult<SqliteDatabase> {
+    let database = SqliteDatabase::open(&config.database_path)?;
+    if database.is_imported()? {
+        return Ok(database);
+    }
 
-    let config_service = create_default_config_service()?;
-    let (_, watcher_registry_path) = config_service.get_registry_paths()?;
-    
     let file_system = Arc::new(RealFileSystem);
-    let watcher_repo = Arc::new(FileWatcherRepository::new(
-        file_system,
-        watcher_registry_path,
-    ));
+    let projects = FileProjectRepository::new(
+        file_system.clone(),
+        config.registry_path.clone(),
+        config.watcher_registry_path.clone(),
+    )
+    .find_all()?;
+    let watchers =
+        FileWatcherRepository::new(file_system.clone(), config.watcher_registry_path.clone())
+            .find_all()?;
+    let history = FileHistoryRepository::new(file_system, config.history_path.clone()).load_history()?;
 
-    Ok(WatcherService::new(watcher_repo))
-}
\ No newline at end of file
+    if database.import(&projects, &watchers, &history)? {
+        info!(
+            "Imported {} projects and {} watchers into {}",
+            projects.len(),
+            watchers.len(),
+            config.database_path.display()
+        );
+    }
+    Ok(database)
+}
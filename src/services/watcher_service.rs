@@ -3,11 +3,11 @@ use crate::types::*;
 use crate::repositories::WatcherRepository;
 
 
-pub struct WatcherService<W: WatcherRepository> {
+pub struct WatcherService<W: WatcherRepository + ?Sized> {
     watcher_repo: Arc<W>,
 }
 
-impl<W: WatcherRepository> WatcherService<W> {
+impl<W: WatcherRepository + ?Sized> WatcherService<W> {
     pub fn new(watcher_repo: Arc<W>) -> Self {
         Self { watcher_repo }
     }
@@ -24,17 +24,33 @@ impl<W: WatcherRepository> WatcherService<W> {
         self.watcher_repo.save(watcher)
     }
 
+    /// Replaces only the health of a stored watcher, keeping its configuration as it is on disk.
+    pub fn update_health(&self, name: &WatcherName, health: WatcherHealth) -> ProjectsResult<bool> {
+        self.watcher_repo.save_health(name, health)
+    }
+
+    pub fn import_watchers(&self, watchers: Vec<WatcherConfig>, mode: ImportMode) -> ProjectsResult<()> {
+        self.watcher_repo.import(watchers, mode)
+    }
+
     pub fn remove_watcher(&self, name: &WatcherName) -> ProjectsResult<bool> {
         self.watcher_repo.remove(name)
     }
 
-    #[allow(dead_code)]
+    /// Replaces the watcher called `name` with `watcher` in one write, `false` if there is none.
+    pub fn rename_watcher(&self, name: &WatcherName, watcher: WatcherConfig) -> ProjectsResult<bool> {
+        self.watcher_repo.rename(name, watcher)
+    }
+
     pub fn watcher_exists(&self, name: &WatcherName) -> ProjectsResult<bool> {
         self.watcher_repo.exists(name)
     }
 
     pub fn remove_all_watchers(&self) -> ProjectsResult<usize> {
-        self.watcher_repo.remove_all_watchers().map(|_| 0)
+        self.watcher_repo
+            .find_all()
+            .map(|watchers| watchers.len())
+            .and_then(|count| self.watcher_repo.remove_all_watchers().map(|_| count))
     }
 }
 
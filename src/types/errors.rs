@@ -1,5 +1,5 @@
 use thiserror::Error;
-use crate::types::types::{ProjectId, WatcherName};
+use crate::types::types::{BackupTimestamp, ProjectId, ProjectName, WatcherName};
 
 #[derive(Error, Debug)]
 #[allow(dead_code)]
@@ -13,15 +13,68 @@ pub enum ProjectsError {
     #[error("RON parsing error: {0}")]
     RonSpanned(#[from] ron::error::SpannedError),
 
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("TOML serialization error: {0}")]
+    TomlSerialization(#[from] toml::ser::Error),
+
+    #[error("TOML parsing error: {0}")]
+    TomlParsing(#[from] toml::de::Error),
+
     #[error("Cargo metadata error: {0}")]
     CargoMetadata(#[from] cargo_metadata::Error),
 
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
     #[error("Project not found with ID: {id}")]
     ProjectNotFound { id: ProjectId },
 
+    #[error("No project is tracked at {}", path.display())]
+    ProjectPathNotTracked { path: std::path::PathBuf },
+
+    #[error("No project is named '{name}' or has a name starting with it")]
+    ProjectNameNotFound { name: String },
+
+    #[error("'{query}' matches several projects, pick one by ID or path:\n  {}", candidates.join("\n  "))]
+    AmbiguousProject {
+        query: String,
+        /// `<id> <name> (<path>)` of every match
+        candidates: Vec<String>,
+    },
+
+    #[error("Project {id} {name} is pinned, unpin it or pass --force to clean it")]
+    ProjectPinned { id: ProjectId, name: ProjectName },
+
     #[error("Watcher not found: {name}")]
     WatcherNotFound { name: WatcherName },
 
+    #[error("A watcher named {name} already exists")]
+    WatcherAlreadyExists { name: WatcherName },
+
+    #[error(
+        "{} was written by a newer cargo-projects (schema version {found}, this binary supports {supported}), please upgrade",
+        path.display()
+    )]
+    SchemaTooNew {
+        path: std::path::PathBuf,
+        found: u32,
+        supported: u32,
+    },
+
+    #[error("Cannot tell the format of {}, pass --format json, toml or ron", path.display())]
+    UnknownRegistryFormat { path: std::path::PathBuf },
+
+    #[error("Cannot move a project to {path}, another project is tracked there")]
+    RelocationTargetTracked { path: std::path::PathBuf },
+
+    #[error("No backup taken at or before {timestamp}, see `cargo projects backup list`")]
+    BackupNotFound { timestamp: BackupTimestamp },
+
+    #[error("The SQLite storage backend is not backed up, backups only cover the RON files")]
+    BackupsNotUsed,
+
     #[error("Registry file not found at: {path}")]
     RegistryNotFound { path: String },
 
@@ -31,15 +84,36 @@ pub enum ProjectsError {
     #[error("Invalid file size: {size}")]
     InvalidFileSize { size: String },
 
+    #[error("Invalid profile name '{name}', use letters, digits, '-', '_' and '.'")]
+    InvalidProfileName { name: String },
+
     #[error("Config directory not found")]
     ConfigDirectoryNotFound,
 
+    #[error("Home directory not found")]
+    HomeDirectoryNotFound,
+
     #[error("Cargo command failed: {stderr}")]
     CargoCommandFailed { stderr: String },
 
     #[error("File watching error: {0}")]
     FileWatching(#[from] notify::Error),
 
+    #[error("Invalid glob pattern '{pattern}': {source}")]
+    InvalidGlob {
+        pattern: String,
+        source: globset::Error,
+    },
+
+    #[error("Daemon is not running")]
+    DaemonNotRunning,
+
+    #[error("Daemon error: {message}")]
+    Daemon { message: String },
+
+    #[error("systemctl failed: {stderr}")]
+    SystemctlFailed { stderr: String },
+
     #[error("Build time parsing error: {message}")]
     BuildTimeParsingError { message: String },
 
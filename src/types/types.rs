@@ -1,8 +1,8 @@
 use byte_unit::{Byte, Unit};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, BTreeSet, HashMap},
     convert::Infallible,
     fmt,
     fs,
@@ -35,6 +35,10 @@ pub struct DependencyCount(usize);
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct WatcherName(String);
 
+/// A label grouping projects by purpose, e.g. `game` or `scratch`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Tag(String);
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum ProjectType {
     /// Regular package with [package] section
@@ -64,10 +68,46 @@ pub struct RustProject {
     pub estimated_build_time_seconds: TimingDuration,
     #[serde(default)]
     pub project_type: ProjectType,
+    #[serde(default)]
+    pub annotations: ProjectAnnotations,
+    /// Crates of a workspace other than its root package, tracked as part of the workspace
+    #[serde(default)]
+    pub members: Vec<WorkspaceMember>,
 }
 
+/// A workspace crate. Its size counts its own sources only, the shared `target/` belongs to the
+/// workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceMember {
+    pub name: ProjectName,
+    pub path: PathBuf,
+    pub version: ProjectVersion,
+    pub size_bytes: FileSize,
+}
+
+/// What the user attached to a project. Rescans replace everything else about a project, but
+/// keep these.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectAnnotations {
+    #[serde(default)]
+    pub tags: BTreeSet<Tag>,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Cleaning the project needs `--force`
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// Layout version of `registry.ron` written by this binary
+pub const PROJECT_REGISTRY_VERSION: u32 = 1;
+/// Layout version of `watchers.ron` written by this binary
+pub const WATCHER_REGISTRY_VERSION: u32 = 1;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProjectRegistry {
+    /// Files written before versioning was introduced have no version and count as 0
+    #[serde(default)]
+    pub schema_version: u32,
     pub projects: HashMap<PathBuf, RustProject>,
     pub last_updated: DateTime<Utc>,
     pub next_id: ProjectId,
@@ -78,15 +118,202 @@ pub struct WatcherConfig {
     pub name: WatcherName,
     pub path: PathBuf,
     pub created_at: DateTime<Utc>,
+    /// Created by `watch --system-wide` and replaced whenever discovery runs again
+    #[serde(default)]
+    pub system_managed: bool,
+    #[serde(default)]
+    pub rules: WatchRules,
+    #[serde(default)]
+    pub mode: WatchMode,
+    /// Paused watchers stay registered but the daemon does not watch them
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub health: WatcherHealth,
+}
+
+/// How the daemon learns about changes below a watcher path
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WatchMode {
+    /// inotify, `FSEvents` or `ReadDirectoryChangesW`
+    #[default]
+    Native,
+    /// Periodic rescans, for NFS or SSHFS mounts and containers where native events never arrive
+    Poll { interval_secs: u64 },
+}
+
+/// What the daemon last observed for a watcher
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatcherHealth {
+    #[serde(default)]
+    pub last_event_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub last_error_at: Option<DateTime<Utc>>,
+    /// New projects the daemon found below the watcher path
+    #[serde(default)]
+    pub projects_discovered: usize,
+    /// Watched by polling, either configured or as a fallback from native events
+    #[serde(default)]
+    pub polling: bool,
+}
+
+/// Which part of a watcher's directory tree is searched for projects
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchRules {
+    /// Globs a project directory must match; empty means every project
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Globs for directories that are never searched
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Deepest `Cargo.toml` below the watcher path, counted like `find -maxdepth`
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+    #[serde(default)]
+    pub follow_symlinks: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WatcherRegistry {
+    #[serde(default)]
+    pub schema_version: u32,
     pub watchers: HashMap<String, WatcherConfig>,
     pub last_updated: DateTime<Utc>,
 }
 
+/// Layout version of `history.ron` written by this binary
+pub const SIZE_HISTORY_VERSION: u32 = 1;
+/// Snapshots closer together than this share one entry holding the latest sizes
+pub const SNAPSHOT_INTERVAL: chrono::TimeDelta = chrono::TimeDelta::minutes(10);
+/// Oldest snapshots beyond this many per project are dropped
+pub const MAX_SNAPSHOTS_PER_PROJECT: usize = 2000;
+
+/// Sizes of a project at one point in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeSnapshot {
+    pub recorded_at: DateTime<Utc>,
+    pub size_bytes: FileSize,
+    pub target_size_bytes: FileSize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SizeHistory {
+    #[serde(default)]
+    pub schema_version: u32,
+    pub projects: HashMap<ProjectId, Vec<SizeSnapshot>>,
+}
+
+/// Projects and watchers written by `export` and read back by `import`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegistryExport {
+    pub schema_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub projects: Vec<RustProject>,
+    pub watchers: Vec<WatcherConfig>,
+}
+
+/// How `import` combines imported projects and watchers with the tracked ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Keep what is tracked, a tracked path keeps its id and imported ids are kept when free
+    Merge,
+    /// Forget every tracked project and watcher and take the imported ones with their ids
+    Replace,
+}
+
+/// Moves paths below `from` to the same place below `to`, written as `FROM=TO`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathRewrite {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// When a backup was taken, written as `20261017T171717Z` in backup file names and on the command
+/// line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BackupTimestamp(DateTime<Utc>);
+
+/// `+tag` or a bare `tag` adds the tag, `-tag` removes it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagEdit {
+    Add(Tag),
+    Remove(Tag),
+}
+
+/// A project given on the command line: an id, a path such as `.`, or its package name or an
+/// unambiguous prefix of it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectRef {
+    Id(ProjectId),
+    Path(PathBuf),
+    Name(String),
+}
+
+/// Why `prune` drops a project
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PruneReason {
+    /// The project directory no longer exists
+    Missing,
+    /// The directory exists but has no `Cargo.toml` anymore
+    NotCargo,
+}
+
 // Implementations for domain types
+impl SizeSnapshot {
+    pub fn of(project: &RustProject) -> Self {
+        Self {
+            recorded_at: Utc::now(),
+            size_bytes: project.size_bytes,
+            target_size_bytes: project.target_size_bytes,
+        }
+    }
+
+    /// Adds the snapshot to the end of `snapshots`. Unchanged sizes are not recorded again, and
+    /// sizes recorded within `SNAPSHOT_INTERVAL` of the last entry update that entry instead.
+    pub fn append_to(self, snapshots: &mut Vec<Self>) {
+        match snapshots.last_mut() {
+            Some(last)
+                if last.size_bytes == self.size_bytes
+                    && last.target_size_bytes == self.target_size_bytes => {}
+            Some(last) if self.recorded_at - last.recorded_at < SNAPSHOT_INTERVAL => {
+                last.size_bytes = self.size_bytes;
+                last.target_size_bytes = self.target_size_bytes;
+            }
+            _ => snapshots.push(self),
+        }
+
+        if snapshots.len() > MAX_SNAPSHOTS_PER_PROJECT {
+            snapshots.drain(..snapshots.len() - MAX_SNAPSHOTS_PER_PROJECT);
+        }
+    }
+}
+
+impl Default for SizeHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SizeHistory {
+    pub fn new() -> Self {
+        Self {
+            schema_version: SIZE_HISTORY_VERSION,
+            projects: HashMap::new(),
+        }
+    }
+
+    /// Moves the snapshots of renumbered projects to their new ids.
+    pub fn renumber(&mut self, changes: &[(ProjectId, ProjectId)]) {
+        let moved: Vec<_> = changes
+            .iter()
+            .filter_map(|(old, new)| self.projects.remove(old).map(|snapshots| (*new, snapshots)))
+            .collect();
+        self.projects.extend(moved);
+    }
+}
+
 impl ProjectId {
     pub const fn new(id: u32) -> Self {
         Self(id)
@@ -118,6 +345,20 @@ impl ProjectName {
     pub const fn new(name: String) -> Self {
         Self(name)
     }
+
+    /// Whether the name is `query`, ignoring case and treating `-` and `_` alike as cargo does.
+    pub fn is(&self, query: &str) -> bool {
+        normalize_name(&self.0) == normalize_name(query)
+    }
+
+    /// Whether the name starts with `query`, compared like `is`.
+    pub fn starts_with(&self, query: &str) -> bool {
+        normalize_name(&self.0).starts_with(&normalize_name(query))
+    }
+}
+
+fn normalize_name(name: &str) -> String {
+    name.to_lowercase().replace('_', "-")
 }
 
 impl fmt::Display for ProjectName {
@@ -220,6 +461,183 @@ impl FromStr for WatcherName {
     }
 }
 
+impl Tag {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Tag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let valid = s.chars().next().is_some_and(char::is_alphanumeric)
+            && s.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'));
+        valid
+            .then(|| Self(s.to_lowercase()))
+            .ok_or_else(|| format!("invalid tag '{s}', use letters, digits, '-', '_', '.' and '/'"))
+    }
+}
+
+impl TagEdit {
+    pub fn apply(&self, tags: &mut BTreeSet<Tag>) {
+        match self {
+            Self::Add(tag) => {
+                tags.insert(tag.clone());
+            }
+            Self::Remove(tag) => {
+                tags.remove(tag);
+            }
+        }
+    }
+}
+
+impl FromStr for TagEdit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_at_checked(1) {
+            Some(("+", tag)) => tag.parse().map(Self::Add),
+            Some(("-", tag)) => tag.parse().map(Self::Remove),
+            _ => s.parse().map(Self::Add),
+        }
+    }
+}
+
+impl RustProject {
+    pub const fn is_workspace(&self) -> bool {
+        matches!(
+            self.project_type,
+            ProjectType::PureWorkspace | ProjectType::WorkspaceWithPackage
+        )
+    }
+
+    pub fn has_member(&self, path: &Path) -> bool {
+        self.members.iter().any(|member| member.path == path)
+    }
+
+    /// Moves the project and its members to the paths `move_path` gives for them.
+    #[must_use]
+    pub fn with_paths_mapped(self, move_path: impl Fn(&Path) -> PathBuf) -> Self {
+        Self {
+            path: move_path(&self.path),
+            members: self
+                .members
+                .into_iter()
+                .map(|member| WorkspaceMember {
+                    path: move_path(&member.path),
+                    ..member
+                })
+                .collect(),
+            ..self
+        }
+    }
+}
+
+impl ProjectAnnotations {
+    /// Adds the tags, pin and note of a project folded into this one, keeping this note if any.
+    pub fn absorb(&mut self, other: Self) {
+        self.tags.extend(other.tags);
+        self.pinned |= other.pinned;
+        if self.note.is_none() {
+            self.note = other.note;
+        }
+    }
+
+    /// Whether the project carries every one of `tags`.
+    pub fn has_tags(&self, tags: &[Tag]) -> bool {
+        tags.iter().all(|tag| self.tags.contains(tag))
+    }
+}
+
+impl PathRewrite {
+    /// `path` moved below `to`, `None` when it is not below `from`.
+    pub fn apply(&self, path: &Path) -> Option<PathBuf> {
+        path.strip_prefix(&self.from).ok().map(|relative| {
+            if relative.as_os_str().is_empty() {
+                self.to.clone()
+            } else {
+                self.to.join(relative)
+            }
+        })
+    }
+}
+
+impl FromStr for PathRewrite {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_once('=')
+            .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+            .map(|(from, to)| Self {
+                from: PathBuf::from(from),
+                to: PathBuf::from(to),
+            })
+            .ok_or_else(|| format!("expected FROM=TO, got '{s}'"))
+    }
+}
+
+impl BackupTimestamp {
+    const FORMAT: &'static str = "%Y%m%dT%H%M%SZ";
+
+    /// The current time, truncated to the seconds kept in the written form.
+    pub fn now() -> Self {
+        Self(Utc::now().with_nanosecond(0).unwrap_or_else(Utc::now))
+    }
+
+    pub const fn get(self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+impl fmt::Display for BackupTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.format(Self::FORMAT))
+    }
+}
+
+impl FromStr for BackupTimestamp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        chrono::NaiveDateTime::parse_from_str(s, Self::FORMAT)
+            .map(|time| Self(time.and_utc()))
+            .map_err(|_| format!("expected a backup timestamp like 20261017T171717Z, got '{s}'"))
+    }
+}
+
+impl FromStr for ProjectRef {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let is_path = matches!(s, "." | "..") || s.contains('/') || s.contains(std::path::MAIN_SEPARATOR);
+        Ok(match s.parse() {
+            Ok(id) => Self::Id(id),
+            Err(_) if is_path => Self::Path(PathBuf::from(s)),
+            Err(_) => Self::Name(s.to_string()),
+        })
+    }
+}
+
+impl PruneReason {
+    /// Why the project at `path` should be pruned, `None` while it is still a cargo project.
+    pub fn of(path: &Path) -> Option<Self> {
+        if !path.is_dir() {
+            Some(Self::Missing)
+        } else if !path.join("Cargo.toml").is_file() {
+            Some(Self::NotCargo)
+        } else {
+            None
+        }
+    }
+}
+
 impl Default for ProjectRegistry {
     fn default() -> Self {
         Self::new()
@@ -229,18 +647,107 @@ impl Default for ProjectRegistry {
 impl ProjectRegistry {
     pub fn new() -> Self {
         Self {
+            schema_version: PROJECT_REGISTRY_VERSION,
             projects: HashMap::new(),
             last_updated: Utc::now(),
             next_id: ProjectId::new(1),
         }
     }
 
-    pub fn add_project(&mut self, mut project: RustProject) {
-        project.id = self.next_id;
-        self.next_id = self.next_id.next();
+    /// Inserts or replaces the project at its path. A path that is already tracked keeps its id
+    /// and annotations, only new paths get the next id.
+    pub fn add_project(&mut self, mut project: RustProject) -> ProjectId {
+        if let Some(existing) = self.projects.get(&project.path) {
+            project.id = existing.id;
+            project.annotations = existing.annotations.clone();
+        } else {
+            project.id = self.next_id;
+            self.next_id = self.next_id.next();
+        }
 
+        let id = project.id;
         self.projects.insert(project.path.clone(), project);
         self.last_updated = Utc::now();
+        id
+    }
+
+    /// Lets `edit` change the annotations of the project with `id`, returning the edited project.
+    pub fn annotate(&mut self, id: ProjectId, edit: &dyn Fn(&mut ProjectAnnotations)) -> Option<RustProject> {
+        let project = self.projects.values_mut().find(|project| project.id == id)?;
+        edit(&mut project.annotations);
+        let project = project.clone();
+        self.last_updated = Utc::now();
+        Some(project)
+    }
+
+    /// Adds a project from another registry. A tracked path keeps its id and a new path keeps its
+    /// imported id unless another project has it. Returns whether the project got the next id
+    /// because its imported id was taken.
+    pub fn merge_project(&mut self, mut project: RustProject) -> bool {
+        let mut renumbered = false;
+        if let Some(existing) = self.projects.get(&project.path) {
+            project.id = existing.id;
+        } else if self.projects.values().any(|other| other.id == project.id) {
+            project.id = self.next_id;
+            renumbered = true;
+        }
+
+        self.next_id = self.next_id.max(project.id.next());
+        self.projects.insert(project.path.clone(), project);
+        self.last_updated = Utc::now();
+        renumbered
+    }
+
+    /// Gives the projects the ids `1..=n` in their current id order, returning the old and new
+    /// id of every project whose id changed.
+    pub fn renumber(&mut self) -> Vec<(ProjectId, ProjectId)> {
+        let mut projects: Vec<&mut RustProject> = self.projects.values_mut().collect();
+        projects.sort_by_key(|project| project.id);
+
+        let mut next_id = ProjectId::new(1);
+        let mut changed = Vec::new();
+        for project in projects {
+            if project.id != next_id {
+                changed.push((project.id, next_id));
+                project.id = next_id;
+            }
+            next_id = next_id.next();
+        }
+
+        self.next_id = next_id;
+        self.last_updated = Utc::now();
+        changed
+    }
+
+    /// Moves every project at or below `from` under `to`, keeping ids and everything else about them.
+    /// Refuses the whole move when a project would land on the path of a project staying put.
+    pub fn relocate_projects(&mut self, from: &Path, to: &Path) -> crate::types::ProjectsResult<usize> {
+        let moves: Vec<(PathBuf, PathBuf)> = self
+            .projects
+            .keys()
+            .filter(|path| path.starts_with(from))
+            .map(|path| (path.clone(), relocated_path(path, from, to)))
+            .collect();
+        if let Some((_, taken)) = moves.iter().find(|(_, new_path)| {
+            self.projects.contains_key(new_path) && !moves.iter().any(|(old_path, _)| old_path == new_path)
+        }) {
+            return Err(crate::types::ProjectsError::RelocationTargetTracked { path: taken.clone() });
+        }
+
+        let projects: Vec<(RustProject, PathBuf)> = moves
+            .iter()
+            .filter_map(|(old_path, new_path)| {
+                self.projects.remove(old_path).map(|project| (project, new_path.clone()))
+            })
+            .collect();
+        for (project, new_path) in projects {
+            self.projects.insert(new_path, project.with_paths_mapped(|path| relocated_path(path, from, to)));
+        }
+
+        if !moves.is_empty() {
+            self.last_updated = Utc::now();
+        }
+        Ok(moves.len())
     }
 
     pub fn find_project_containing_path(&self, file_path: &Path) -> Option<&RustProject> {
@@ -303,6 +810,14 @@ impl ProjectRegistry {
     }
 }
 
+/// Where `path`, at or below `from`, ends up when `from` is moved to `to`.
+pub fn relocated_path(path: &Path, from: &Path, to: &Path) -> PathBuf {
+    path.strip_prefix(from)
+        .ok()
+        .filter(|relative| !relative.as_os_str().is_empty())
+        .map_or_else(|| to.to_path_buf(), |relative| to.join(relative))
+}
+
 impl Default for WatcherRegistry {
     fn default() -> Self {
         Self::new()
@@ -312,6 +827,7 @@ impl Default for WatcherRegistry {
 impl WatcherRegistry {
     pub fn new() -> Self {
         Self {
+            schema_version: WATCHER_REGISTRY_VERSION,
             watchers: HashMap::new(),
             last_updated: Utc::now(),
         }
@@ -323,6 +839,11 @@ impl WatcherRegistry {
             name: name.clone(),
             path,
             created_at: Utc::now(),
+            system_managed: false,
+            rules: WatchRules::default(),
+            mode: WatchMode::Native,
+            paused: false,
+            health: WatcherHealth::default(),
         };
         self.watchers.insert(name.as_str().to_string(), config);
         self.last_updated = Utc::now();
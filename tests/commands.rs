@@ -1,7 +1,20 @@
+use std::collections::BTreeSet;
 use std::path::PathBuf;
 use cargo_projects::types::*;
 use cargo_projects::commands::*;
 use chrono::Utc;
+use std::sync::OnceLock;
+
+/// Points every factory at a throwaway config directory, so the tests never touch the registry of
+/// the user running them.
+fn use_test_config_dir() {
+    static CONFIG_DIR: OnceLock<tempfile::TempDir> = OnceLock::new();
+    let config_dir = CONFIG_DIR.get_or_init(|| tempfile::tempdir().unwrap());
+    cargo_projects::services::set_config_location(cargo_projects::services::ConfigLocation {
+        dir: Some(config_dir.path().to_path_buf()),
+        profile: None,
+    });
+}
 
 fn create_test_project(name: &str, path: PathBuf) -> RustProject {
     RustProject {
@@ -16,6 +29,8 @@ fn create_test_project(name: &str, path: PathBuf) -> RustProject {
         dependencies_count: DependencyCount::new(5),
         estimated_build_time_seconds: TimingDuration::new(30),
         project_type: ProjectType::Package,
+        annotations: ProjectAnnotations::default(),
+        members: Vec::new(),
     }
 }
 
@@ -24,12 +39,71 @@ fn create_test_watcher(name: &str, path: PathBuf) -> WatcherConfig {
         name: WatcherName::new(name.to_string()),
         path,
         created_at: Utc::now(),
+        system_managed: false,
+        rules: WatchRules::default(),
+        mode: WatchMode::Native,
+        paused: false,
+        health: WatcherHealth::default(),
+    }
+}
+
+/// A project service over RON files in `dir`, for tests that must not share the test config.
+fn file_project_service(
+    dir: &std::path::Path,
+) -> cargo_projects::services::ProjectService<
+    cargo_projects::repositories::FileProjectRepository<cargo_projects::infrastructure::RealFileSystem>,
+    cargo_projects::repositories::FileWatcherRepository<cargo_projects::infrastructure::RealFileSystem>,
+> {
+    use cargo_projects::infrastructure::RealFileSystem;
+    use cargo_projects::repositories::{
+        FileHistoryRepository, FileProjectRepository, FileWatcherRepository,
+    };
+    use std::sync::Arc;
+
+    let file_system = Arc::new(RealFileSystem);
+    cargo_projects::services::ProjectService::new(
+        Arc::new(FileProjectRepository::new(
+            file_system.clone(),
+            dir.join("registry.ron"),
+            dir.join("watchers.ron"),
+        )),
+        Arc::new(FileWatcherRepository::new(file_system.clone(), dir.join("watchers.ron"))),
+        Arc::new(FileHistoryRepository::new(file_system, dir.join("history.ron"))),
+    )
+}
+
+/// A workspace `ws` with a root package and the members `ws-a` and `ws-b` below `crates/`.
+fn create_test_workspace(dir: &std::path::Path) -> std::io::Result<PathBuf> {
+    let root = dir.join("ws");
+    std::fs::create_dir_all(root.join("src"))?;
+    std::fs::write(root.join("src/main.rs"), "fn main() {}")?;
+    std::fs::write(root.join("Cargo.toml"), r#"
+[package]
+name = "ws"
+version = "0.1.0"
+edition = "2021"
+
+[workspace]
+members = ["crates/*"]
+"#)?;
+    for member in ["a", "b"] {
+        let member_dir = root.join("crates").join(member);
+        std::fs::create_dir_all(member_dir.join("src"))?;
+        std::fs::write(member_dir.join("src/lib.rs"), "")?;
+        std::fs::write(member_dir.join("Cargo.toml"), format!(r#"
+[package]
+name = "ws-{member}"
+version = "0.2.0"
+edition = "2021"
+"#))?;
     }
+    root.canonicalize()
 }
 
 #[test]
 fn test_list_projects_empty_registry() -> ProjectsResult<()> {
-    let result = handle_list_projects(None)?;
+    use_test_config_dir();
+    let result = handle_list_projects(None, &[])?;
     
     assert!(result.total_count >= 0);
     assert_eq!(result.projects.len(), result.total_count);
@@ -38,8 +112,9 @@ fn test_list_projects_empty_registry() -> ProjectsResult<()> {
 
 #[test]
 fn test_list_projects_with_nonexistent_watcher() -> ProjectsResult<()> {
+    use_test_config_dir();
     let watcher_name = WatcherName::new("nonexistent-watcher".to_string());
-    let result = handle_list_projects(Some(&watcher_name))?;
+    let result = handle_list_projects(Some(&watcher_name), &[])?;
     
     assert_eq!(result.projects.len(), result.total_count);
     Ok(())
@@ -47,9 +122,10 @@ fn test_list_projects_with_nonexistent_watcher() -> ProjectsResult<()> {
 
 #[test]
 fn test_handle_clean_project_not_found() -> ProjectsResult<()> {
+    use_test_config_dir();
     let non_existent_id = ProjectId::new(999999999); 
     
-    match handle_clean_project(non_existent_id) {
+    match handle_clean_project(&ProjectRef::Id(non_existent_id), false) {
         Ok(_) => {
             // If it succeeds, the project existed and was cleaned
         }
@@ -61,8 +137,20 @@ fn test_handle_clean_project_not_found() -> ProjectsResult<()> {
     Ok(())
 }
 
+#[test]
+fn test_handle_remove_watcher_not_found() {
+    use_test_config_dir();
+    let watcher_name = WatcherName::new("nonexistent-watcher-to-remove".to_string());
+
+    assert!(matches!(
+        handle_remove_watcher(&watcher_name),
+        Err(ProjectsError::WatcherNotFound { .. })
+    ));
+}
+
 #[test]
 fn test_handle_list_watchers() -> ProjectsResult<()> {
+    use_test_config_dir();
     let result = handle_list_watchers()?;
     
     assert!(result.watchers.len() >= 0);
@@ -71,6 +159,7 @@ fn test_handle_list_watchers() -> ProjectsResult<()> {
 
 #[test]
 fn test_handle_clean_watchers() -> ProjectsResult<()> {
+    use_test_config_dir();
     let result = handle_clean_watchers();
     
     assert!(result.is_ok());
@@ -79,6 +168,7 @@ fn test_handle_clean_watchers() -> ProjectsResult<()> {
 
 #[test]
 fn test_handle_update_projects() -> ProjectsResult<()> {
+    use_test_config_dir();
     let result = handle_update_projects()?;
     
     assert!(result.total_updated >= 0);
@@ -96,8 +186,46 @@ mod integration_tests {
     use super::*;
     use std::fs;
 
+    #[test]
+    fn test_concurrent_repository_writes_keep_every_project() -> ProjectsResult<()> {
+        use cargo_projects::infrastructure::RealFileSystem;
+        use cargo_projects::repositories::{FileProjectRepository, ProjectRepository};
+        use std::sync::Arc;
+
+        let temp_dir = tempfile::tempdir()?;
+        let registry_path = temp_dir.path().join("registry.ron");
+        let watcher_registry_path = temp_dir.path().join("watchers.ron");
+
+        std::thread::scope(|scope| {
+            for writer in 0..4 {
+                let repository = FileProjectRepository::new(
+                    Arc::new(RealFileSystem),
+                    registry_path.clone(),
+                    watcher_registry_path.clone(),
+                );
+                scope.spawn(move || {
+                    for index in 0..10 {
+                        let path = PathBuf::from(format!("/projects/{writer}-{index}"));
+                        repository
+                            .save(create_test_project(&format!("{writer}-{index}"), path))
+                            .unwrap();
+                    }
+                });
+            }
+        });
+
+        let repository = FileProjectRepository::new(
+            Arc::new(RealFileSystem),
+            registry_path,
+            watcher_registry_path,
+        );
+        assert_eq!(repository.find_all()?.len(), 40);
+        Ok(())
+    }
+
     #[test] 
     fn test_scan_directory_integration() -> ProjectsResult<()> {
+        use_test_config_dir();
         let temp_dir = tempfile::tempdir()?;
         let project_dir = temp_dir.path().join("test-project");
         fs::create_dir_all(&project_dir)?;
@@ -117,8 +245,176 @@ edition = "2021"
         Ok(())
     }
     
+    #[test]
+    fn test_failed_import_keeps_the_size_history() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let service = file_project_service(temp_dir.path());
+        service.add_project(create_test_project("game", PathBuf::from("/dev/game")))?;
+        let id = service.find_project_by_path(&PathBuf::from("/dev/game"))?.unwrap().id;
+        assert_eq!(service.get_size_history(id)?.len(), 1);
+
+        fs::write(temp_dir.path().join("registry.ron"), "not a registry")?;
+        let imported = vec![create_test_project("tool", PathBuf::from("/dev/tool"))];
+        assert!(service.import_projects(imported, ImportMode::Replace).is_err());
+        assert_eq!(service.get_size_history(id)?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_project_by_name() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let service = file_project_service(temp_dir.path());
+        service.add_projects(vec![
+            create_test_project("bevy", PathBuf::from("/dev/bevy")),
+            create_test_project("bevy_game", PathBuf::from("/dev/bevy-game")),
+            create_test_project("firmware", PathBuf::from("/dev/firmware")),
+        ])?;
+        let resolve = |name: &str| service.resolve_project(&name.parse().unwrap());
+
+        assert_eq!(resolve("bevy")?.path, PathBuf::from("/dev/bevy"));
+        assert_eq!(resolve("bevy-g")?.path, PathBuf::from("/dev/bevy-game"));
+        assert_eq!(resolve("FIRM")?.id, ProjectId::new(3));
+        assert!(matches!(resolve("missing"), Err(ProjectsError::ProjectNameNotFound { .. })));
+
+        service.add_project(create_test_project("firmware", PathBuf::from("/dev/fork/firmware")))?;
+        match resolve("firmware") {
+            Err(ProjectsError::AmbiguousProject { candidates, .. }) => {
+                assert_eq!(candidates, ["3 firmware (/dev/firmware)", "4 firmware (/dev/fork/firmware)"]);
+            }
+            other => panic!("expected an ambiguous match, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_tracked_members_fold_into_their_workspace() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let service = file_project_service(temp_dir.path());
+        let mut render = create_test_project("bevy_render", PathBuf::from("/dev/bevy/crates/render"));
+        render.annotations.pinned = true;
+        render.annotations.tags.insert("gpu".parse().unwrap());
+        service.add_projects(vec![
+            render,
+            create_test_project("firmware", PathBuf::from("/dev/firmware")),
+        ])?;
+
+        let mut bevy = create_test_project("bevy", PathBuf::from("/dev/bevy"));
+        bevy.project_type = ProjectType::WorkspaceWithPackage;
+        bevy.members = ["render", "ecs"]
+            .into_iter()
+            .map(|name| WorkspaceMember {
+                name: ProjectName::new(format!("bevy_{name}")),
+                path: PathBuf::from(format!("/dev/bevy/crates/{name}")),
+                version: ProjectVersion::new("1.0.0".to_string()),
+                size_bytes: FileSize::new(1024),
+            })
+            .collect();
+        let ecs = create_test_project("bevy_ecs", PathBuf::from("/dev/bevy/crates/ecs"));
+        let added = service.add_projects_with_members(vec![bevy, ecs])?;
+        assert_eq!(added.len(), 1);
+
+        let projects = service.get_all_projects()?;
+        let names: BTreeSet<String> = projects.iter().map(|p| p.name.to_string()).collect();
+        assert_eq!(names, BTreeSet::from(["bevy".to_string(), "firmware".to_string()]));
+        let bevy = projects.iter().find(|p| p.name.is("bevy")).unwrap();
+        assert_eq!(bevy.members.len(), 2);
+        assert!(bevy.annotations.pinned);
+        assert!(bevy.annotations.has_tags(&["gpu".parse().unwrap()]));
+
+        let rescanned = create_test_project("bevy_ecs", PathBuf::from("/dev/bevy/crates/ecs"));
+        assert!(service.add_projects_with_members(vec![rescanned])?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_workspace_members_come_from_cargo_metadata() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let root = create_test_workspace(temp_dir.path())?;
+
+        let members = read_workspace_members(&root);
+        let names: Vec<String> = members.iter().map(|member| member.name.to_string()).collect();
+        assert_eq!(names, ["ws-a", "ws-b"]);
+        assert_eq!(members[0].path, root.join("crates/a"));
+        assert_eq!(members[0].version, ProjectVersion::new("0.2.0".to_string()));
+        assert!(members[0].size_bytes.bytes() > 0);
+        // A member is not the root of the workspace it belongs to
+        assert_eq!(read_workspace_members(&root.join("crates/a")), Vec::new());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_folds_members_tracked_before_workspaces_were() -> ProjectsResult<()> {
+        use_test_config_dir();
+        let temp_dir = tempfile::tempdir()?;
+        let root = create_test_workspace(temp_dir.path())?;
+        let service = file_project_service(temp_dir.path());
+
+        let mut workspace = create_test_project("ws", root.clone());
+        workspace.project_type = ProjectType::WorkspaceWithPackage;
+        let mut member = create_test_project("ws-a", root.join("crates/a"));
+        member.annotations.note = Some("renderer".to_string());
+        service.add_projects(vec![workspace, member, create_test_project("ws-b", root.join("crates/b"))])?;
+
+        let paths = vec![root.clone(), root.join("crates/a"), root.join("crates/b")];
+        let result = cargo_projects::commands::scan::track_project_paths(&service, paths)?;
+        assert_eq!(result.added_count, 0);
+
+        let projects = service.get_all_projects()?;
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].path, root);
+        assert_eq!(projects[0].members.len(), 2);
+        assert_eq!(projects[0].annotations.note.as_deref(), Some("renderer"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pinned_projects_are_not_cleaned() -> ProjectsResult<()> {
+        use_test_config_dir();
+        let temp_dir = tempfile::tempdir()?;
+        let project_dir = temp_dir.path().join("pinned-game");
+        fs::create_dir_all(project_dir.join("src"))?;
+        fs::write(project_dir.join("src/main.rs"), "fn main() {}")?;
+        fs::write(project_dir.join("Cargo.toml"), r#"
+[package]
+name = "pinned-game"
+version = "0.1.0"
+edition = "2021"
+"#)?;
+        fs::create_dir_all(project_dir.join("target"))?;
+
+        handle_scan_directory(temp_dir.path())?;
+        let project = ProjectRef::Path(project_dir.clone());
+        let tag: Tag = "pinned-test".parse().unwrap();
+        handle_tag_project(&project, &[TagEdit::Add(tag.clone())])?;
+        assert!(handle_pin_project(&project, true)?.annotations.pinned);
+
+        assert!(matches!(
+            handle_clean_project(&project, false),
+            Err(ProjectsError::ProjectPinned { .. })
+        ));
+        let result = handle_clean_tagged_projects(std::slice::from_ref(&tag), false)?;
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(result.cleaned.len(), 0);
+        assert!(project_dir.join("target").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_reason() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let project_dir = temp_dir.path().join("game");
+        fs::create_dir_all(&project_dir)?;
+
+        assert_eq!(PruneReason::of(&project_dir), Some(PruneReason::NotCargo));
+        fs::write(project_dir.join("Cargo.toml"), "[package]")?;
+        assert_eq!(PruneReason::of(&project_dir), None);
+        assert_eq!(PruneReason::of(&temp_dir.path().join("gone")), Some(PruneReason::Missing));
+        Ok(())
+    }
+
     #[test]
     fn test_scan_directory_no_projects() -> ProjectsResult<()> {
+        use_test_config_dir();
         let temp_dir = tempfile::tempdir()?;
         
         let result = handle_scan_directory(temp_dir.path())?;
@@ -131,6 +427,7 @@ edition = "2021"
 
     #[test]
     fn test_scan_directory_malformed_cargo_toml() -> ProjectsResult<()> {
+        use_test_config_dir();
         let temp_dir = tempfile::tempdir()?;
         let project_dir = temp_dir.path().join("malformed-project");
         fs::create_dir_all(&project_dir)?;
@@ -183,6 +480,263 @@ mod unit_tests {
         assert_ne!(id1, id2);
     }
 
+    #[test]
+    fn test_registry_relocate_projects_keeps_ids() -> ProjectsResult<()> {
+        let mut registry = ProjectRegistry::new();
+        registry.add_project(create_test_project("game", PathBuf::from("/dev/games/game")));
+        registry.add_project(create_test_project("tool", PathBuf::from("/dev/tools/tool")));
+        let game_id = registry.projects[&PathBuf::from("/dev/games/game")].id;
+
+        let moved = registry.relocate_projects(&PathBuf::from("/dev/games"), &PathBuf::from("/old/games"))?;
+
+        assert_eq!(moved, 1);
+        let relocated = &registry.projects[&PathBuf::from("/old/games/game")];
+        assert_eq!(relocated.id, game_id);
+        assert_eq!(relocated.path, PathBuf::from("/old/games/game"));
+        assert!(registry.projects.contains_key(&PathBuf::from("/dev/tools/tool")));
+        assert!(!registry.projects.contains_key(&PathBuf::from("/dev/games/game")));
+
+        // Moving onto a tracked project would silently replace it
+        assert!(matches!(
+            registry.relocate_projects(&PathBuf::from("/old/games/game"), &PathBuf::from("/dev/tools/tool")),
+            Err(ProjectsError::RelocationTargetTracked { .. })
+        ));
+        assert_eq!(registry.projects[&PathBuf::from("/old/games/game")].id, game_id);
+        assert_eq!(registry.projects.len(), 2);
+        Ok(())
+    }
+
+    /// A RON and a SQLite project repository in `dir`, for tests both backends must pass.
+    fn project_backends(
+        dir: &std::path::Path,
+    ) -> ProjectsResult<Vec<Box<dyn cargo_projects::repositories::ProjectRepository>>> {
+        use cargo_projects::infrastructure::RealFileSystem;
+        use cargo_projects::repositories::{FileProjectRepository, SqliteDatabase, SqliteProjectRepository};
+        use std::sync::Arc;
+
+        Ok(vec![
+            Box::new(FileProjectRepository::new(
+                Arc::new(RealFileSystem),
+                dir.join("registry.ron"),
+                dir.join("watchers.ron"),
+            )),
+            Box::new(SqliteProjectRepository::new(Arc::new(SqliteDatabase::open(
+                &dir.join("registry.sqlite3"),
+            )?))),
+        ])
+    }
+
+    #[test]
+    fn test_relocating_a_workspace_moves_its_members() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        for projects in project_backends(temp_dir.path())? {
+            let mut workspace = create_test_project("ws", PathBuf::from("/dev/ws"));
+            workspace.project_type = ProjectType::WorkspaceWithPackage;
+            workspace.members.push(WorkspaceMember {
+                name: ProjectName::new("ws-a".to_string()),
+                path: PathBuf::from("/dev/ws/crates/a"),
+                version: ProjectVersion::new("0.2.0".to_string()),
+                size_bytes: FileSize::new(100),
+            });
+            projects.save(workspace)?;
+
+            assert_eq!(projects.relocate(&PathBuf::from("/dev"), &PathBuf::from("/src"))?, 1);
+
+            let moved = projects.find_by_path(&PathBuf::from("/src/ws"))?.unwrap();
+            assert_eq!(moved.members[0].path, PathBuf::from("/src/ws/crates/a"));
+            assert!(moved.has_member(&PathBuf::from("/src/ws/crates/a")));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_all_returns_the_tracked_ids() -> ProjectsResult<()> {
+        let temp_dir = tempfile::tempdir()?;
+        for projects in project_backends(temp_dir.path())? {
+            let ids = projects.save_all(vec![
+                create_test_project("game", PathBuf::from("/dev/game")),
+                create_test_project("tool", PathBuf::from("/dev/tool")),
+                create_test_project("lib", PathBuf::from("/dev/lib")),
+            ])?;
+
+            let removed = projects.remove_all(&[ids[0], ids[2], ProjectId::new(99)])?;
+
+            assert_eq!(removed.into_iter().collect::<BTreeSet<_>>(), BTreeSet::from([ids[0], ids[2]]));
+            assert_eq!(projects.find_all()?.len(), 1);
+            assert_eq!(projects.remove_all(&[ids[0]])?, Vec::new());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_renaming_a_watcher_leaves_one_watcher_on_its_path() -> ProjectsResult<()> {
+        use cargo_projects::infrastructure::RealFileSystem;
+        use cargo_projects::repositories::{FileWatcherRepository, SqliteDatabase, SqliteWatcherRepository, WatcherRepository};
+        use std::sync::Arc;
+
+        let temp_dir = tempfile::tempdir()?;
+        let backends: Vec<Box<dyn WatcherRepository>> = vec![
+            Box::new(FileWatcherRepository::new(Arc::new(RealFileSystem), temp_dir.path().join("watchers.ron"))),
+            Box::new(SqliteWatcherRepository::new(Arc::new(SqliteDatabase::open(
+                &temp_dir.path().join("registry.sqlite3"),
+            )?))),
+        ];
+
+        for watchers in backends {
+            let old_name = WatcherName::new("dev".to_string());
+            watchers.save(create_test_watcher("dev", PathBuf::from("/dev")))?;
+
+            let renamed = create_test_watcher("code", PathBuf::from("/dev"));
+            assert!(watchers.rename(&old_name, renamed.clone())?);
+
+            let stored = watchers.find_all()?;
+            assert_eq!(stored.len(), 1);
+            assert_eq!(stored[0].name, renamed.name);
+            assert!(!watchers.rename(&old_name, renamed)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_registry_keeps_ids_of_tracked_paths() {
+        let mut registry = ProjectRegistry::new();
+        registry.add_project(create_test_project("game", PathBuf::from("/dev/game")));
+        registry.add_project(create_test_project("tool", PathBuf::from("/dev/tool")));
+
+        let mut updated = create_test_project("game", PathBuf::from("/dev/game"));
+        updated.size_bytes = FileSize::new(2000);
+        registry.add_project(updated);
+
+        let game = &registry.projects[&PathBuf::from("/dev/game")];
+        assert_eq!(game.id, ProjectId::new(1));
+        assert_eq!(game.size_bytes.bytes(), 2000);
+        assert_eq!(registry.next_id, ProjectId::new(3));
+    }
+
+    #[test]
+    fn test_registry_keeps_annotations_of_rescanned_projects() {
+        let mut registry = ProjectRegistry::new();
+        let id = registry.add_project(create_test_project("game", PathBuf::from("/dev/game")));
+        let tag: Tag = "game".parse().unwrap();
+
+        let annotated = registry.annotate(id, &|annotations| {
+            annotations.tags.insert(tag.clone());
+            annotations.note = Some("jam entry".to_string());
+        });
+        assert_eq!(annotated.map(|project| project.annotations.tags.len()), Some(1));
+        assert!(registry.annotate(ProjectId::new(9), &|_| {}).is_none());
+
+        registry.add_project(create_test_project("game", PathBuf::from("/dev/game")));
+        let game = &registry.projects[&PathBuf::from("/dev/game")];
+        assert!(game.annotations.has_tags(std::slice::from_ref(&tag)));
+        assert_eq!(game.annotations.note.as_deref(), Some("jam entry"));
+    }
+
+    #[test]
+    fn test_registry_renumber_closes_gaps_in_order() {
+        let mut registry = ProjectRegistry::new();
+        for name in ["a", "b", "c"] {
+            registry.add_project(create_test_project(name, PathBuf::from(format!("/dev/{name}"))));
+        }
+        registry.projects.remove(&PathBuf::from("/dev/a"));
+
+        assert_eq!(registry.renumber().len(), 2);
+        assert_eq!(registry.projects[&PathBuf::from("/dev/b")].id, ProjectId::new(1));
+        assert_eq!(registry.projects[&PathBuf::from("/dev/c")].id, ProjectId::new(2));
+        assert_eq!(registry.next_id, ProjectId::new(3));
+        assert_eq!(registry.renumber(), []);
+    }
+
+    #[test]
+    fn test_size_snapshots_merge_within_interval() {
+        let start = Utc::now();
+        let snapshot = |minutes, bytes| SizeSnapshot {
+            recorded_at: start + chrono::TimeDelta::minutes(minutes),
+            size_bytes: FileSize::new(bytes),
+            target_size_bytes: FileSize::new(bytes / 2),
+        };
+        let mut snapshots = Vec::new();
+
+        snapshot(0, 1000).append_to(&mut snapshots);
+        snapshot(30, 1000).append_to(&mut snapshots);
+        snapshot(35, 2000).append_to(&mut snapshots);
+        assert_eq!(snapshots, vec![snapshot(0, 1000), snapshot(35, 2000)]);
+
+        snapshot(40, 3000).append_to(&mut snapshots);
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[1].recorded_at, snapshot(35, 0).recorded_at);
+        assert_eq!(snapshots[1].size_bytes.bytes(), 3000);
+    }
+
+    #[test]
+    fn test_registry_merge_keeps_free_imported_ids() {
+        let mut registry = ProjectRegistry::new();
+        registry.add_project(create_test_project("mine", PathBuf::from("/dev/mine")));
+
+        let mut taken = create_test_project("game", PathBuf::from("/dev/game"));
+        taken.id = ProjectId::new(1);
+        let mut free = create_test_project("tool", PathBuf::from("/dev/tool"));
+        free.id = ProjectId::new(7);
+        let mut tracked = create_test_project("mine", PathBuf::from("/dev/mine"));
+        tracked.id = ProjectId::new(9);
+
+        assert!(registry.merge_project(taken));
+        assert!(!registry.merge_project(free));
+        // Taking the id its path already has is not a new id
+        assert!(!registry.merge_project(tracked));
+        assert_eq!(registry.projects[&PathBuf::from("/dev/game")].id, ProjectId::new(2));
+        assert_eq!(registry.projects[&PathBuf::from("/dev/tool")].id, ProjectId::new(7));
+        assert_eq!(registry.projects[&PathBuf::from("/dev/mine")].id, ProjectId::new(1));
+        assert_eq!(registry.next_id, ProjectId::new(8));
+    }
+
+    #[test]
+    fn test_path_rewrite() {
+        let rewrite: PathRewrite = "/home/alice=/home/bob".parse().unwrap();
+
+        assert_eq!(
+            rewrite.apply(&PathBuf::from("/home/alice/dev/game")),
+            Some(PathBuf::from("/home/bob/dev/game"))
+        );
+        assert_eq!(rewrite.apply(&PathBuf::from("/home/alice")), Some(PathBuf::from("/home/bob")));
+        assert_eq!(rewrite.apply(&PathBuf::from("/home/alicia/dev")), None);
+        assert!("/home/alice".parse::<PathRewrite>().is_err());
+    }
+
+    #[test]
+    fn test_project_ref_parses_ids_paths_and_names() {
+        assert_eq!("42".parse::<ProjectRef>(), Ok(ProjectRef::Id(ProjectId::new(42))));
+        assert_eq!(
+            "../games/snake".parse::<ProjectRef>(),
+            Ok(ProjectRef::Path(PathBuf::from("../games/snake")))
+        );
+        assert_eq!(".".parse::<ProjectRef>(), Ok(ProjectRef::Path(PathBuf::from("."))));
+        assert_eq!("snake".parse::<ProjectRef>(), Ok(ProjectRef::Name("snake".to_string())));
+    }
+
+    #[test]
+    fn test_project_name_matching() {
+        let name = ProjectName::new("Bevy_Game".to_string());
+
+        assert!(name.is("bevy-game"));
+        assert!(!name.is("bevy"));
+        assert!(name.starts_with("bevy-g"));
+        assert!(!name.starts_with("game"));
+    }
+
+    #[test]
+    fn test_tag_edits() {
+        let mut tags = BTreeSet::new();
+        for edit in ["+Game", "scratch", "-scratch", "-archived"] {
+            edit.parse::<TagEdit>().unwrap().apply(&mut tags);
+        }
+
+        assert_eq!(tags.into_iter().map(|tag| tag.to_string()).collect::<Vec<_>>(), ["game"]);
+        assert!("+".parse::<TagEdit>().is_err());
+        assert!("two words".parse::<TagEdit>().is_err());
+        assert!("-".parse::<Tag>().is_err());
+    }
+
     #[test]
     fn test_file_size_operations() {
         let size1 = FileSize::new(1000);